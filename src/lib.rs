@@ -16,17 +16,53 @@
 //!
 //! See the [README of the latest version][3] for more details.
 //!
+//! This crate is `no_std` but requires `alloc`. Its dependencies are used
+//! without their `std` features, so it works on targets that only have
+//! `alloc`, with or without the `compiled_data` feature.
+//!
 //! [1]: https://docs.rs/crate/idna/latest
 //! [2]: https://internals.rust-lang.org/t/pre-rfc-mutually-excusive-global-features/19618
 //! [3]: https://docs.rs/crate/idna_adapter/latest
 
 #![no_std]
 
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+use icu_collections::codepointtrie::TrieValue;
 use icu_normalizer::properties::CanonicalCombiningClassMap;
 use icu_normalizer::uts46::Uts46Mapper;
+use icu_normalizer::ComposingNormalizer;
+use icu_normalizer::DecomposingNormalizer;
+use icu_normalizer::NormalizerError;
+use icu_properties::maps::CodePointMapData;
+#[cfg(feature = "compiled_data")]
 use icu_properties::maps::CodePointMapDataBorrowed;
+use icu_properties::script::ScriptWithExtensions;
+use icu_properties::script::ScriptWithExtensionsBorrowed;
+use icu_properties::sets::CodePointSetData;
+#[cfg(feature = "compiled_data")]
+use icu_properties::sets::CodePointSetDataBorrowed;
 use icu_properties::CanonicalCombiningClass;
 use icu_properties::GeneralCategory;
+use icu_properties::IndicSyllabicCategory;
+use icu_properties::PropertiesError;
+use icu_provider::DataProvider;
+
+/// The version of Unicode, as (major, minor, patch), of the data that this
+/// back end uses.
+///
+/// ICU4X doesn't expose its Unicode version programmatically, so this is
+/// hard-coded. The version requirements of `icu_normalizer` and
+/// `icu_properties` are resolved independently, so the two could come from
+/// different ICU4X releases. All releases that the requirements currently
+/// allow (ICU4X 1.4 and 1.5) use Unicode 15.1, and the tests of this crate
+/// probe both data sources for the characters added in that version.
+pub const UNICODE_VERSION: (u8, u8, u8) = (15, 1, 0);
 
 /// Turns a joining type into a mask for comparing with multiple type at once.
 const fn joining_type_to_mask(jt: icu_properties::JoiningType) -> u32 {
@@ -117,12 +153,234 @@ const MARK_MASK: u32 = general_category_to_mask(GeneralCategory::NonspacingMark)
     | general_category_to_mask(GeneralCategory::SpacingMark)
     | general_category_to_mask(GeneralCategory::EnclosingMark);
 
+/// Mask for checking for any of the Letter general categories.
+pub const LETTER_MASK: GeneralCategoryMask = GeneralCategoryMask(
+    general_category_to_mask(GeneralCategory::UppercaseLetter)
+        | general_category_to_mask(GeneralCategory::LowercaseLetter)
+        | general_category_to_mask(GeneralCategory::TitlecaseLetter)
+        | general_category_to_mask(GeneralCategory::ModifierLetter)
+        | general_category_to_mask(GeneralCategory::OtherLetter),
+);
+
+/// Mask for checking for any of the Punctuation general categories.
+pub const PUNCTUATION_MASK: GeneralCategoryMask = GeneralCategoryMask(
+    general_category_to_mask(GeneralCategory::ConnectorPunctuation)
+        | general_category_to_mask(GeneralCategory::DashPunctuation)
+        | general_category_to_mask(GeneralCategory::OpenPunctuation)
+        | general_category_to_mask(GeneralCategory::ClosePunctuation)
+        | general_category_to_mask(GeneralCategory::InitialPunctuation)
+        | general_category_to_mask(GeneralCategory::FinalPunctuation)
+        | general_category_to_mask(GeneralCategory::OtherPunctuation),
+);
+
+/// Mask for checking for any of the Symbol general categories.
+pub const SYMBOL_MASK: GeneralCategoryMask = GeneralCategoryMask(
+    general_category_to_mask(GeneralCategory::MathSymbol)
+        | general_category_to_mask(GeneralCategory::CurrencySymbol)
+        | general_category_to_mask(GeneralCategory::ModifierSymbol)
+        | general_category_to_mask(GeneralCategory::OtherSymbol),
+);
+
+/// Bidi_Class values for ASCII, which dominates real input, so that
+/// [`Adapter::bidi_class`] can avoid the back end lookup for it.
+const BIDI_ASCII: [u8; 128] = {
+    use icu_properties::BidiClass as B;
+    [
+        B::BoundaryNeutral.0,    // 00
+        B::BoundaryNeutral.0,    // 01
+        B::BoundaryNeutral.0,    // 02
+        B::BoundaryNeutral.0,    // 03
+        B::BoundaryNeutral.0,    // 04
+        B::BoundaryNeutral.0,    // 05
+        B::BoundaryNeutral.0,    // 06
+        B::BoundaryNeutral.0,    // 07
+        B::BoundaryNeutral.0,    // 08
+        B::SegmentSeparator.0,   // 09
+        B::ParagraphSeparator.0, // 0A
+        B::SegmentSeparator.0,   // 0B
+        B::WhiteSpace.0,         // 0C
+        B::ParagraphSeparator.0, // 0D
+        B::BoundaryNeutral.0,    // 0E
+        B::BoundaryNeutral.0,    // 0F
+        B::BoundaryNeutral.0,    // 10
+        B::BoundaryNeutral.0,    // 11
+        B::BoundaryNeutral.0,    // 12
+        B::BoundaryNeutral.0,    // 13
+        B::BoundaryNeutral.0,    // 14
+        B::BoundaryNeutral.0,    // 15
+        B::BoundaryNeutral.0,    // 16
+        B::BoundaryNeutral.0,    // 17
+        B::BoundaryNeutral.0,    // 18
+        B::BoundaryNeutral.0,    // 19
+        B::BoundaryNeutral.0,    // 1A
+        B::BoundaryNeutral.0,    // 1B
+        B::ParagraphSeparator.0, // 1C
+        B::ParagraphSeparator.0, // 1D
+        B::ParagraphSeparator.0, // 1E
+        B::SegmentSeparator.0,   // 1F
+        B::WhiteSpace.0,         // 20
+        B::OtherNeutral.0,       // 21
+        B::OtherNeutral.0,       // 22
+        B::EuropeanTerminator.0, // 23
+        B::EuropeanTerminator.0, // 24
+        B::EuropeanTerminator.0, // 25
+        B::OtherNeutral.0,       // 26
+        B::OtherNeutral.0,       // 27
+        B::OtherNeutral.0,       // 28
+        B::OtherNeutral.0,       // 29
+        B::OtherNeutral.0,       // 2A
+        B::EuropeanSeparator.0,  // 2B
+        B::CommonSeparator.0,    // 2C
+        B::EuropeanSeparator.0,  // 2D
+        B::CommonSeparator.0,    // 2E
+        B::CommonSeparator.0,    // 2F
+        B::EuropeanNumber.0,     // 30
+        B::EuropeanNumber.0,     // 31
+        B::EuropeanNumber.0,     // 32
+        B::EuropeanNumber.0,     // 33
+        B::EuropeanNumber.0,     // 34
+        B::EuropeanNumber.0,     // 35
+        B::EuropeanNumber.0,     // 36
+        B::EuropeanNumber.0,     // 37
+        B::EuropeanNumber.0,     // 38
+        B::EuropeanNumber.0,     // 39
+        B::CommonSeparator.0,    // 3A
+        B::OtherNeutral.0,       // 3B
+        B::OtherNeutral.0,       // 3C
+        B::OtherNeutral.0,       // 3D
+        B::OtherNeutral.0,       // 3E
+        B::OtherNeutral.0,       // 3F
+        B::OtherNeutral.0,       // 40
+        B::LeftToRight.0,        // 41
+        B::LeftToRight.0,        // 42
+        B::LeftToRight.0,        // 43
+        B::LeftToRight.0,        // 44
+        B::LeftToRight.0,        // 45
+        B::LeftToRight.0,        // 46
+        B::LeftToRight.0,        // 47
+        B::LeftToRight.0,        // 48
+        B::LeftToRight.0,        // 49
+        B::LeftToRight.0,        // 4A
+        B::LeftToRight.0,        // 4B
+        B::LeftToRight.0,        // 4C
+        B::LeftToRight.0,        // 4D
+        B::LeftToRight.0,        // 4E
+        B::LeftToRight.0,        // 4F
+        B::LeftToRight.0,        // 50
+        B::LeftToRight.0,        // 51
+        B::LeftToRight.0,        // 52
+        B::LeftToRight.0,        // 53
+        B::LeftToRight.0,        // 54
+        B::LeftToRight.0,        // 55
+        B::LeftToRight.0,        // 56
+        B::LeftToRight.0,        // 57
+        B::LeftToRight.0,        // 58
+        B::LeftToRight.0,        // 59
+        B::LeftToRight.0,        // 5A
+        B::OtherNeutral.0,       // 5B
+        B::OtherNeutral.0,       // 5C
+        B::OtherNeutral.0,       // 5D
+        B::OtherNeutral.0,       // 5E
+        B::OtherNeutral.0,       // 5F
+        B::OtherNeutral.0,       // 60
+        B::LeftToRight.0,        // 61
+        B::LeftToRight.0,        // 62
+        B::LeftToRight.0,        // 63
+        B::LeftToRight.0,        // 64
+        B::LeftToRight.0,        // 65
+        B::LeftToRight.0,        // 66
+        B::LeftToRight.0,        // 67
+        B::LeftToRight.0,        // 68
+        B::LeftToRight.0,        // 69
+        B::LeftToRight.0,        // 6A
+        B::LeftToRight.0,        // 6B
+        B::LeftToRight.0,        // 6C
+        B::LeftToRight.0,        // 6D
+        B::LeftToRight.0,        // 6E
+        B::LeftToRight.0,        // 6F
+        B::LeftToRight.0,        // 70
+        B::LeftToRight.0,        // 71
+        B::LeftToRight.0,        // 72
+        B::LeftToRight.0,        // 73
+        B::LeftToRight.0,        // 74
+        B::LeftToRight.0,        // 75
+        B::LeftToRight.0,        // 76
+        B::LeftToRight.0,        // 77
+        B::LeftToRight.0,        // 78
+        B::LeftToRight.0,        // 79
+        B::LeftToRight.0,        // 7A
+        B::OtherNeutral.0,       // 7B
+        B::OtherNeutral.0,       // 7C
+        B::OtherNeutral.0,       // 7D
+        B::OtherNeutral.0,       // 7E
+        B::BoundaryNeutral.0,    // 7F
+    ]
+};
+
+/// `true` iff `c` is one of the four label separators of UTS 46: U+002E
+/// FULL STOP, U+3002 IDEOGRAPHIC FULL STOP, U+FF0E FULLWIDTH FULL STOP, or
+/// U+FF61 HALFWIDTH IDEOGRAPHIC FULL STOP.
+#[inline(always)]
+fn is_label_separator(c: char) -> bool {
+    matches!(c, '.' | '\u{3002}' | '\u{FF0E}' | '\u{FF61}')
+}
+
+/// `true` iff `c` is one of the four deviation characters of UTS 46: U+00DF
+/// LATIN SMALL LETTER SHARP S, U+03C2 GREEK SMALL LETTER FINAL SIGMA,
+/// U+200C ZERO WIDTH NON-JOINER, or U+200D ZERO WIDTH JOINER.
+#[inline(always)]
+fn is_deviation(c: char) -> bool {
+    matches!(c, '\u{00DF}' | '\u{03C2}' | '\u{200C}' | '\u{200D}')
+}
+
+/// `true` iff `input` contains an uppercase ASCII letter.
+#[inline(always)]
+fn has_ascii_uppercase(input: &str) -> bool {
+    input.bytes().any(|b| b.is_ascii_uppercase())
+}
+
+/// `true` iff `c` is not allowed in mapped output with
+/// UseSTD3ASCIIRules=true: ASCII characters other than lowercase letters,
+/// digits, U+002D HYPHEN-MINUS, and U+002E FULL STOP, as well as the
+/// _disallowed_STD3_valid_ characters U+2260 NOT EQUAL TO, U+226E NOT
+/// LESS-THAN, and U+226F NOT GREATER-THAN.
+#[inline(always)]
+fn is_std3_disallowed(c: char) -> bool {
+    if c.is_ascii() {
+        return !matches!(c, 'a'..='z' | '0'..='9' | '-' | '.');
+    }
+    matches!(c, '\u{2260}' | '\u{226E}' | '\u{226F}')
+}
+
+/// Every Joining_Type value. Returned by [`Adapter::all_joining_types`].
+static ALL_JOINING_TYPES: [JoiningType; 6] = [
+    JoiningType::JOIN_CAUSING,
+    JoiningType::DUAL_JOINING,
+    JoiningType::RIGHT_JOINING,
+    JoiningType::LEFT_JOINING,
+    JoiningType::TRANSPARENT,
+    JoiningType::NON_JOINING,
+];
+
 /// Value for the Joining_Type Unicode property.
 #[repr(transparent)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct JoiningType(icu_properties::JoiningType);
 
 impl JoiningType {
+    /// Join_Causing (C)
+    pub const JOIN_CAUSING: JoiningType = JoiningType(icu_properties::JoiningType::JoinCausing);
+    /// Dual_Joining (D)
+    pub const DUAL_JOINING: JoiningType = JoiningType(icu_properties::JoiningType::DualJoining);
+    /// Right_Joining (R)
+    pub const RIGHT_JOINING: JoiningType = JoiningType(icu_properties::JoiningType::RightJoining);
+    /// Left_Joining (L)
+    pub const LEFT_JOINING: JoiningType = JoiningType(icu_properties::JoiningType::LeftJoining);
+    /// Transparent (T)
+    pub const TRANSPARENT: JoiningType = JoiningType(icu_properties::JoiningType::Transparent);
+    /// Non_Joining (U)
+    pub const NON_JOINING: JoiningType = JoiningType(icu_properties::JoiningType::NonJoining);
+
     /// Returns the corresponding `JoiningTypeMask`.
     #[inline(always)]
     pub fn to_mask(self) -> JoiningTypeMask {
@@ -150,9 +408,36 @@ impl JoiningTypeMask {
     }
 }
 
+/// Every Bidi_Class value. Returned by [`Adapter::all_bidi_classes`].
+static ALL_BIDI_CLASSES: [BidiClass; 23] = [
+    BidiClass(icu_properties::BidiClass::LeftToRight),
+    BidiClass(icu_properties::BidiClass::RightToLeft),
+    BidiClass(icu_properties::BidiClass::EuropeanNumber),
+    BidiClass(icu_properties::BidiClass::EuropeanSeparator),
+    BidiClass(icu_properties::BidiClass::EuropeanTerminator),
+    BidiClass(icu_properties::BidiClass::ArabicNumber),
+    BidiClass(icu_properties::BidiClass::CommonSeparator),
+    BidiClass(icu_properties::BidiClass::ParagraphSeparator),
+    BidiClass(icu_properties::BidiClass::SegmentSeparator),
+    BidiClass(icu_properties::BidiClass::WhiteSpace),
+    BidiClass(icu_properties::BidiClass::OtherNeutral),
+    BidiClass(icu_properties::BidiClass::LeftToRightEmbedding),
+    BidiClass(icu_properties::BidiClass::LeftToRightOverride),
+    BidiClass(icu_properties::BidiClass::ArabicLetter),
+    BidiClass(icu_properties::BidiClass::RightToLeftEmbedding),
+    BidiClass(icu_properties::BidiClass::RightToLeftOverride),
+    BidiClass(icu_properties::BidiClass::PopDirectionalFormat),
+    BidiClass(icu_properties::BidiClass::NonspacingMark),
+    BidiClass(icu_properties::BidiClass::BoundaryNeutral),
+    BidiClass(icu_properties::BidiClass::FirstStrongIsolate),
+    BidiClass(icu_properties::BidiClass::LeftToRightIsolate),
+    BidiClass(icu_properties::BidiClass::RightToLeftIsolate),
+    BidiClass(icu_properties::BidiClass::PopDirectionalIsolate),
+];
+
 /// Value for the Bidi_Class Unicode property.
 #[repr(transparent)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct BidiClass(icu_properties::BidiClass);
 
 impl BidiClass {
@@ -199,15 +484,457 @@ impl BidiClassMask {
     pub fn intersects(self, other: BidiClassMask) -> bool {
         self.0 & other.0 != 0
     }
+
+    /// Returns the classes that are in `self` but not in `allowed`, e.g.
+    /// for reporting the disallowed classes found in a label.
+    #[inline(always)]
+    pub fn difference(self, allowed: BidiClassMask) -> BidiClassMask {
+        BidiClassMask(self.0 & !allowed.0)
+    }
+}
+
+/// Value for the Script Unicode property.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Script(icu_properties::Script);
+
+impl Script {
+    /// Common (Zyyy)
+    pub const COMMON: Script = Script(icu_properties::Script::Common);
+    /// Inherited (Zinh)
+    pub const INHERITED: Script = Script(icu_properties::Script::Inherited);
+    /// Arabic (Arab)
+    pub const ARABIC: Script = Script(icu_properties::Script::Arabic);
+    /// Armenian (Armn)
+    pub const ARMENIAN: Script = Script(icu_properties::Script::Armenian);
+    /// Bengali (Beng)
+    pub const BENGALI: Script = Script(icu_properties::Script::Bengali);
+    /// Bopomofo (Bopo)
+    pub const BOPOMOFO: Script = Script(icu_properties::Script::Bopomofo);
+    /// Cyrillic (Cyrl)
+    pub const CYRILLIC: Script = Script(icu_properties::Script::Cyrillic);
+    /// Devanagari (Deva)
+    pub const DEVANAGARI: Script = Script(icu_properties::Script::Devanagari);
+    /// Georgian (Geor)
+    pub const GEORGIAN: Script = Script(icu_properties::Script::Georgian);
+    /// Greek (Grek)
+    pub const GREEK: Script = Script(icu_properties::Script::Greek);
+    /// Han (Hani)
+    pub const HAN: Script = Script(icu_properties::Script::Han);
+    /// Hangul (Hang)
+    pub const HANGUL: Script = Script(icu_properties::Script::Hangul);
+    /// Hebrew (Hebr)
+    pub const HEBREW: Script = Script(icu_properties::Script::Hebrew);
+    /// Hiragana (Hira)
+    pub const HIRAGANA: Script = Script(icu_properties::Script::Hiragana);
+    /// Katakana (Kana)
+    pub const KATAKANA: Script = Script(icu_properties::Script::Katakana);
+    /// Latin (Latn)
+    pub const LATIN: Script = Script(icu_properties::Script::Latin);
+    /// Tamil (Taml)
+    pub const TAMIL: Script = Script(icu_properties::Script::Tamil);
+    /// Thai (Thai)
+    pub const THAI: Script = Script(icu_properties::Script::Thai);
+    /// Japanese (Jpan), used by UTS 39 for Han, Hiragana, and Katakana.
+    /// Not a value of the Script property.
+    pub const JAPANESE: Script = Script(JAPANESE);
+    /// Korean (Kore), used by UTS 39 for Han and Hangul. Not a value of the
+    /// Script property.
+    pub const KOREAN: Script = Script(KOREAN);
+    /// Han with Bopomofo (Hanb), used by UTS 39 for Han and Bopomofo. Not a
+    /// value of the Script property.
+    pub const HAN_WITH_BOPOMOFO: Script = Script(HAN_WITH_BOPOMOFO);
+}
+
+/// The scripts that UTS 39 uses for augmenting Script_Extensions. ICU4X
+/// doesn't name these, so these are ICU4C's `USCRIPT_JAPANESE`,
+/// `USCRIPT_KOREAN`, and `USCRIPT_HAN_WITH_BOPOMOFO`.
+const JAPANESE: icu_properties::Script = icu_properties::Script(105);
+const KOREAN: icu_properties::Script = icu_properties::Script(119);
+const HAN_WITH_BOPOMOFO: icu_properties::Script = icu_properties::Script(172);
+
+/// Mask containing all scripts, which is the starting point for resolving
+/// the scripts of a label with [`Adapter::scripts_compatible`].
+pub const ALL_SCRIPTS_MASK: ScriptMask = ScriptMask([u64::MAX; 4]);
+
+/// A mask representing potentially multiple `Script` values.
+///
+/// The set of all scripts is what UTS 39 uses for the Script_Extensions of
+/// Common and Inherited characters.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ScriptMask([u64; 4]);
+
+impl ScriptMask {
+    const EMPTY: ScriptMask = ScriptMask([0; 4]);
+
+    /// `true` iff `script` is in this mask.
+    #[inline(always)]
+    pub fn contains(self, script: Script) -> bool {
+        self.0
+            .get(usize::from((script.0).0 / 64))
+            .map_or(false, |word| word & (1u64 << ((script.0).0 % 64)) != 0)
+    }
+
+    /// `true` iff both masks have at least one `Script` in common.
+    #[inline(always)]
+    pub fn intersects(self, other: ScriptMask) -> bool {
+        self.intersection(other) != ScriptMask::EMPTY
+    }
+
+    #[inline(always)]
+    fn insert(&mut self, script: icu_properties::Script) {
+        if let Some(word) = self.0.get_mut(usize::from(script.0 / 64)) {
+            *word |= 1u64 << (script.0 % 64);
+        }
+    }
+
+    #[inline(always)]
+    fn remove(&mut self, script: icu_properties::Script) {
+        if let Some(word) = self.0.get_mut(usize::from(script.0 / 64)) {
+            *word &= !(1u64 << (script.0 % 64));
+        }
+    }
+
+    #[inline(always)]
+    fn intersection(self, other: ScriptMask) -> ScriptMask {
+        let mut words = self.0;
+        for (word, other) in words.iter_mut().zip(other.0.iter()) {
+            *word &= other;
+        }
+        ScriptMask(words)
+    }
+
+    /// Returns the script if this set contains exactly one.
+    #[inline(always)]
+    fn single(self) -> Option<icu_properties::Script> {
+        if self.0.iter().map(|word| word.count_ones()).sum::<u32>() != 1 {
+            return None;
+        }
+        let (i, word) = self.0.iter().enumerate().find(|(_, &word)| word != 0)?;
+        Some(icu_properties::Script(
+            (i as u16) * 64 + (word.trailing_zeros() as u16),
+        ))
+    }
+}
+
+/// A mask representing potentially multiple General_Category
+/// values.
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct GeneralCategoryMask(u32);
+
+impl GeneralCategoryMask {
+    /// `true` iff both masks have at least one General_Category in common.
+    #[inline(always)]
+    pub fn intersects(self, other: GeneralCategoryMask) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+/// The direction of a label in a bidi domain as determined by the first
+/// character of the label per rule 1 of RFC 5893.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LabelDirection {
+    /// The first character is Left_To_Right.
+    Ltr,
+    /// The first character is Right_To_Left or Arabic_Letter.
+    Rtl,
+    /// The label is empty or its first character has some other bidi class.
+    Invalid,
+}
+
+/// A Unicode normalization form.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NormalizationForm {
+    /// Normalization Form C (canonical composition)
+    Nfc,
+    /// Normalization Form D (canonical decomposition)
+    Nfd,
+    /// Normalization Form KC (compatibility composition)
+    Nfkc,
+    /// Normalization Form KD (compatibility decomposition)
+    Nfkd,
+}
+
+/// The UTS 46 status of a character.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Uts46Status {
+    Valid,
+    Mapped,
+    Ignored,
+    Deviation,
+    Disallowed,
+}
+
+/// Counts of the UTS 46 statuses of the input characters of a mapping
+/// pass. Returned by [`Adapter::map_normalize_with_stats`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct MappingStats {
+    mapped: usize,
+    ignored: usize,
+    deviation: usize,
+    disallowed: usize,
+}
+
+impl MappingStats {
+    /// The number of _mapped_ input characters.
+    #[inline(always)]
+    pub fn mapped(&self) -> usize {
+        self.mapped
+    }
+
+    /// The number of _ignored_ input characters.
+    #[inline(always)]
+    pub fn ignored(&self) -> usize {
+        self.ignored
+    }
+
+    /// The number of _deviation_ input characters.
+    #[inline(always)]
+    pub fn deviation(&self) -> usize {
+        self.deviation
+    }
+
+    /// The number of _disallowed_ input characters.
+    #[inline(always)]
+    pub fn disallowed(&self) -> usize {
+        self.disallowed
+    }
+}
+
+/// Error returned by [`Adapter::map_normalize_strict_deviation`] when the
+/// input contains a deviation character, whose treatment differs between
+/// transitional and nontransitional processing, or a character that maps
+/// to one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DeviationAmbiguity {
+    character: char,
+    offset: usize,
+}
+
+impl DeviationAmbiguity {
+    /// The first input character that is or maps to a deviation character.
+    #[inline(always)]
+    pub fn character(&self) -> char {
+        self.character
+    }
+
+    /// The byte offset of the first deviation character in the input.
+    #[inline(always)]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl core::fmt::Display for DeviationAmbiguity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "deviation character U+{:04X} at byte offset {}",
+            u32::from(self.character),
+            self.offset
+        )
+    }
+}
+
+/// Error returned by [`Adapter::assert_mapping_invariants`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InvariantViolation {
+    /// Mapping the mapped output again changed it.
+    NotIdempotent,
+    /// The mapped output is not in Normalization Form C.
+    NotNfc,
+}
+
+impl core::fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            InvariantViolation::NotIdempotent => "mapping is not idempotent for the input",
+            InvariantViolation::NotNfc => "mapping output is not in NFC",
+        })
+    }
+}
+
+/// Result of [`Adapter::bidi_domain_info`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BidiDomainInfo {
+    is_bidi: bool,
+    direction: LabelDirection,
+}
+
+impl BidiDomainInfo {
+    /// `true` iff the domain is a bidi domain in the sense of RFC 5893,
+    /// i.e. contains a character whose Bidi_Class is R, AL, or AN.
+    #[inline(always)]
+    pub fn is_bidi(&self) -> bool {
+        self.is_bidi
+    }
+
+    /// The base direction of the domain as determined by its first strong
+    /// (L, R, or AL) character, or [`LabelDirection::Invalid`] if the
+    /// domain has no strong characters.
+    #[inline(always)]
+    pub fn direction(&self) -> LabelDirection {
+        self.direction
+    }
+}
+
+/// Per-label result of [`Adapter::validate_labels`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LabelValidity {
+    mapping_ok: bool,
+    bidi_ok: bool,
+    context_ok: bool,
+}
+
+impl LabelValidity {
+    /// `true` iff the mapped label contains no disallowed characters, with
+    /// UseSTD3ASCIIRules=true.
+    #[inline(always)]
+    pub fn mapping_ok(&self) -> bool {
+        self.mapping_ok
+    }
+
+    /// `true` iff the domain isn't a bidi domain or the label satisfies the
+    /// bidi rule of RFC 5893.
+    #[inline(always)]
+    pub fn bidi_ok(&self) -> bool {
+        self.bidi_ok
+    }
+
+    /// `true` iff the label satisfies the CONTEXTJ and CONTEXTO rules of
+    /// RFC 5892.
+    #[inline(always)]
+    pub fn context_ok(&self) -> bool {
+        self.context_ok
+    }
+
+    /// `true` iff all of the above checks passed.
+    #[inline(always)]
+    pub fn is_valid(&self) -> bool {
+        self.mapping_ok && self.bidi_ok && self.context_ok
+    }
+}
+
+/// Error returned when constructing an [`Adapter`] from a data provider
+/// fails, e.g. because the provider lacks the data for a key.
+#[derive(Debug)]
+pub struct DataLoadError(DataLoadErrorKind);
+
+#[derive(Debug)]
+enum DataLoadErrorKind {
+    Normalizer(NormalizerError),
+    Properties(PropertiesError),
+}
+
+impl DataLoadError {
+    fn normalizer(error: NormalizerError) -> Self {
+        DataLoadError(DataLoadErrorKind::Normalizer(error))
+    }
+
+    fn properties(error: PropertiesError) -> Self {
+        DataLoadError(DataLoadErrorKind::Properties(error))
+    }
+}
+
+impl core::fmt::Display for DataLoadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.0 {
+            DataLoadErrorKind::Normalizer(error) => error.fmt(f),
+            DataLoadErrorKind::Properties(error) => error.fmt(f),
+        }
+    }
+}
+
+/// Code point map data that is either compiled into the binary or loaded
+/// from a data provider at run time.
+enum MapData<T: TrieValue> {
+    #[cfg(feature = "compiled_data")]
+    Compiled(CodePointMapDataBorrowed<'static, T>),
+    Loaded(CodePointMapData<T>),
+}
+
+impl<T: TrieValue> MapData<T> {
+    #[inline(always)]
+    fn get(&self, c: char) -> T {
+        match self {
+            #[cfg(feature = "compiled_data")]
+            MapData::Compiled(data) => data.get(c),
+            MapData::Loaded(data) => data.as_borrowed().get(c),
+        }
+    }
+}
+
+/// Code point set data that is either compiled into the binary or loaded
+/// from a data provider at run time.
+enum SetData {
+    #[cfg(feature = "compiled_data")]
+    Compiled(CodePointSetDataBorrowed<'static>),
+    Loaded(CodePointSetData),
+}
+
+impl SetData {
+    #[inline(always)]
+    fn contains(&self, c: char) -> bool {
+        match self {
+            #[cfg(feature = "compiled_data")]
+            SetData::Compiled(data) => data.contains(c),
+            SetData::Loaded(data) => data.as_borrowed().contains(c),
+        }
+    }
+}
+
+/// Script_Extensions data that is either compiled into the binary or loaded
+/// from a data provider at run time.
+enum ScriptData {
+    #[cfg(feature = "compiled_data")]
+    Compiled(ScriptWithExtensionsBorrowed<'static>),
+    Loaded(ScriptWithExtensions),
+}
+
+impl ScriptData {
+    #[inline(always)]
+    fn borrowed(&self) -> ScriptWithExtensionsBorrowed<'_> {
+        match self {
+            #[cfg(feature = "compiled_data")]
+            ScriptData::Compiled(data) => *data,
+            ScriptData::Loaded(data) => data.as_borrowed(),
+        }
+    }
+}
+
+/// The normalizers for the forms other than NFC that
+/// [`Adapter::map_normalize_form`] supports.
+///
+/// With compiled data, the normalizers are constructed when needed, which
+/// is cheap. Loaded normalizers are boxed, because they are large and
+/// rarely used.
+enum FormNormalizers {
+    #[cfg(feature = "compiled_data")]
+    Compiled,
+    Loaded(Box<LoadedFormNormalizers>),
+}
+
+struct LoadedFormNormalizers {
+    nfd: DecomposingNormalizer,
+    nfkc: ComposingNormalizer,
+    nfkd: DecomposingNormalizer,
 }
 
 /// An adapter between a Unicode back end an the `idna` crate.
 pub struct Adapter {
     mapper: Uts46Mapper,
+    nfc: ComposingNormalizer,
     canonical_combining_class: CanonicalCombiningClassMap,
-    general_category: CodePointMapDataBorrowed<'static, GeneralCategory>,
-    bidi_class: CodePointMapDataBorrowed<'static, icu_properties::BidiClass>,
-    joining_type: CodePointMapDataBorrowed<'static, icu_properties::JoiningType>,
+    general_category: MapData<GeneralCategory>,
+    bidi_class: MapData<icu_properties::BidiClass>,
+    joining_type: MapData<icu_properties::JoiningType>,
+    indic_syllabic_category: MapData<IndicSyllabicCategory>,
+    prepended_concatenation_mark: SetData,
+    full_composition_exclusion: SetData,
+    script_with_extensions: ScriptData,
+    form_normalizers: FormNormalizers,
+    mark_overrides: &'static [(char, bool)],
 }
 
 #[cfg(feature = "compiled_data")]
@@ -224,41 +951,492 @@ impl Adapter {
     pub const fn new() -> Self {
         Self {
             mapper: Uts46Mapper::new(),
+            nfc: ComposingNormalizer::new_nfc(),
             canonical_combining_class: CanonicalCombiningClassMap::new(),
-            general_category: icu_properties::maps::general_category(),
-            bidi_class: icu_properties::maps::bidi_class(),
-            joining_type: icu_properties::maps::joining_type(),
+            general_category: MapData::Compiled(icu_properties::maps::general_category()),
+            bidi_class: MapData::Compiled(icu_properties::maps::bidi_class()),
+            joining_type: MapData::Compiled(icu_properties::maps::joining_type()),
+            indic_syllabic_category: MapData::Compiled(
+                icu_properties::maps::indic_syllabic_category(),
+            ),
+            prepended_concatenation_mark: SetData::Compiled(
+                icu_properties::sets::prepended_concatenation_mark(),
+            ),
+            full_composition_exclusion: SetData::Compiled(
+                icu_properties::sets::full_composition_exclusion(),
+            ),
+            script_with_extensions: ScriptData::Compiled(
+                icu_properties::script::script_with_extensions(),
+            ),
+            form_normalizers: FormNormalizers::Compiled,
+            mark_overrides: &[],
         }
     }
 
+    /// Constructor using data loaded at run time from `provider` instead of
+    /// data compiled into the binary, so the `compiled_data` feature isn't
+    /// needed.
+    ///
+    /// To load the data from a blob, enable the `serde` feature and use
+    /// `try_new_with_buffer_provider`.
+    pub fn try_new_unstable<D>(provider: &D) -> Result<Self, DataLoadError>
+    where
+        D: DataProvider<icu_normalizer::provider::CanonicalDecompositionDataV1Marker>
+            + DataProvider<icu_normalizer::provider::CanonicalDecompositionTablesV1Marker>
+            + DataProvider<icu_normalizer::provider::CompatibilityDecompositionSupplementV1Marker>
+            + DataProvider<icu_normalizer::provider::CompatibilityDecompositionTablesV1Marker>
+            + DataProvider<icu_normalizer::provider::Uts46DecompositionSupplementV1Marker>
+            + DataProvider<icu_normalizer::provider::CanonicalCompositionsV1Marker>
+            + DataProvider<icu_properties::provider::GeneralCategoryV1Marker>
+            + DataProvider<icu_properties::provider::BidiClassV1Marker>
+            + DataProvider<icu_properties::provider::JoiningTypeV1Marker>
+            + DataProvider<icu_properties::provider::IndicSyllabicCategoryV1Marker>
+            + DataProvider<icu_properties::provider::PrependedConcatenationMarkV1Marker>
+            + DataProvider<icu_properties::provider::FullCompositionExclusionV1Marker>
+            + DataProvider<icu_properties::provider::ScriptWithExtensionsPropertyV1Marker>
+            + ?Sized,
+    {
+        Ok(Self {
+            mapper: Uts46Mapper::try_new(provider).map_err(DataLoadError::normalizer)?,
+            nfc: ComposingNormalizer::try_new_nfc_unstable(provider)
+                .map_err(DataLoadError::normalizer)?,
+            canonical_combining_class: CanonicalCombiningClassMap::try_new_unstable(provider)
+                .map_err(DataLoadError::normalizer)?,
+            general_category: MapData::Loaded(
+                icu_properties::maps::load_general_category(provider)
+                    .map_err(DataLoadError::properties)?,
+            ),
+            bidi_class: MapData::Loaded(
+                icu_properties::maps::load_bidi_class(provider)
+                    .map_err(DataLoadError::properties)?,
+            ),
+            joining_type: MapData::Loaded(
+                icu_properties::maps::load_joining_type(provider)
+                    .map_err(DataLoadError::properties)?,
+            ),
+            indic_syllabic_category: MapData::Loaded(
+                icu_properties::maps::load_indic_syllabic_category(provider)
+                    .map_err(DataLoadError::properties)?,
+            ),
+            prepended_concatenation_mark: SetData::Loaded(
+                icu_properties::sets::load_prepended_concatenation_mark(provider)
+                    .map_err(DataLoadError::properties)?,
+            ),
+            full_composition_exclusion: SetData::Loaded(
+                icu_properties::sets::load_full_composition_exclusion(provider)
+                    .map_err(DataLoadError::properties)?,
+            ),
+            script_with_extensions: ScriptData::Loaded(
+                icu_properties::script::load_script_with_extensions_unstable(provider)
+                    .map_err(DataLoadError::properties)?,
+            ),
+            form_normalizers: FormNormalizers::Loaded(Box::new(LoadedFormNormalizers {
+                nfd: DecomposingNormalizer::try_new_nfd_unstable(provider)
+                    .map_err(DataLoadError::normalizer)?,
+                nfkc: ComposingNormalizer::try_new_nfkc_unstable(provider)
+                    .map_err(DataLoadError::normalizer)?,
+                nfkd: DecomposingNormalizer::try_new_nfkd_unstable(provider)
+                    .map_err(DataLoadError::normalizer)?,
+            })),
+            mark_overrides: &[],
+        })
+    }
+
+    /// Constructor using data deserialized at run time from a blob or
+    /// another `BufferProvider`.
+    ///
+    /// A deserialization feature of `icu_provider` for the format of the
+    /// data, such as `deserialize_postcard_1`, needs to be enabled.
+    #[cfg(feature = "serde")]
+    pub fn try_new_with_buffer_provider(
+        provider: &(impl icu_provider::BufferProvider + ?Sized),
+    ) -> Result<Self, DataLoadError> {
+        use icu_provider::AsDeserializingBufferProvider;
+        Self::try_new_unstable(&provider.as_deserializing())
+    }
+
+    /// Replaces the data of this adapter with data loaded from `provider`
+    /// like [`try_new_unstable`][Self::try_new_unstable] does, e.g. to move a
+    /// long-running process to a newer Unicode version without restarting.
+    ///
+    /// The mark overrides set with
+    /// [`with_mark_overrides`][Self::with_mark_overrides] are kept. If
+    /// loading fails, the adapter is left unchanged.
+    pub fn reload<D>(&mut self, provider: &D) -> Result<(), DataLoadError>
+    where
+        D: DataProvider<icu_normalizer::provider::CanonicalDecompositionDataV1Marker>
+            + DataProvider<icu_normalizer::provider::CanonicalDecompositionTablesV1Marker>
+            + DataProvider<icu_normalizer::provider::CompatibilityDecompositionSupplementV1Marker>
+            + DataProvider<icu_normalizer::provider::CompatibilityDecompositionTablesV1Marker>
+            + DataProvider<icu_normalizer::provider::Uts46DecompositionSupplementV1Marker>
+            + DataProvider<icu_normalizer::provider::CanonicalCompositionsV1Marker>
+            + DataProvider<icu_properties::provider::GeneralCategoryV1Marker>
+            + DataProvider<icu_properties::provider::BidiClassV1Marker>
+            + DataProvider<icu_properties::provider::JoiningTypeV1Marker>
+            + DataProvider<icu_properties::provider::IndicSyllabicCategoryV1Marker>
+            + DataProvider<icu_properties::provider::PrependedConcatenationMarkV1Marker>
+            + DataProvider<icu_properties::provider::FullCompositionExclusionV1Marker>
+            + DataProvider<icu_properties::provider::ScriptWithExtensionsPropertyV1Marker>
+            + ?Sized,
+    {
+        let mut reloaded = Self::try_new_unstable(provider)?;
+        reloaded.mark_overrides = self.mark_overrides;
+        *self = reloaded;
+        Ok(())
+    }
+
+    /// Returns this adapter with [`is_mark`][Self::is_mark] overridden for
+    /// the characters listed in `overrides`, replacing any earlier
+    /// overrides. This works regardless of where the data of the adapter
+    /// comes from.
+    ///
+    /// For a character listed in `overrides`, `is_mark` returns the
+    /// associated `bool` instead of consulting the General_Category. If a
+    /// character is listed more than once, the first entry takes
+    /// precedence. The list is searched linearly, so it should be short.
+    #[inline(always)]
+    pub const fn with_mark_overrides(mut self, overrides: &'static [(char, bool)]) -> Self {
+        self.mark_overrides = overrides;
+        self
+    }
+
     /// `true` iff the Canonical_Combining_Class of `c` is Virama.
     #[inline(always)]
     pub fn is_virama(&self, c: char) -> bool {
         self.canonical_combining_class.get(c) == CanonicalCombiningClass::Virama
     }
 
+    /// `true` iff `c` has the Full_Composition_Exclusion property, i.e.
+    /// its canonical decomposition is never recomposed to `c` by NFC.
+    #[inline(always)]
+    pub fn is_full_composition_exclusion(&self, c: char) -> bool {
+        self.full_composition_exclusion.contains(c)
+    }
+
+    /// `true` iff the Canonical_Combining_Class of `c` is Virama or the
+    /// Indic_Syllabic_Category of `c` is Pure_Killer.
+    ///
+    /// Unlike [`is_virama`][Self::is_virama], which implements RFC 5892
+    /// exactly, this also covers pure killers such as U+0E4E THAI CHARACTER
+    /// YAMAKKAN and U+17D1 KHMER SIGN VIRIAM, which don't have CCC 9, for
+    /// callers whose ContextJ interpretation includes them.
+    #[inline(always)]
+    pub fn is_virama_or_pure_killer(&self, c: char) -> bool {
+        self.is_virama(c)
+            || self.indic_syllabic_category.get(c) == IndicSyllabicCategory::PureKiller
+    }
+
+    /// `true` iff any character of `label` has the Canonical_Combining_Class
+    /// Virama.
+    #[inline]
+    pub fn any_virama(&self, label: &[char]) -> bool {
+        label.iter().any(|&c| self.is_virama(c))
+    }
+
     /// `true` iff the General_Category of `c` is Mark, i.e. any of Nonspacing_Mark,
-    /// Spacing_Mark, or Enclosing_Mark.
+    /// Spacing_Mark, or Enclosing_Mark, unless overridden via
+    /// [`with_mark_overrides`][Self::with_mark_overrides].
     #[inline(always)]
     pub fn is_mark(&self, c: char) -> bool {
+        if let Some(&(_, is_mark)) = self.mark_overrides.iter().find(|&&(o, _)| o == c) {
+            return is_mark;
+        }
         (general_category_to_mask(self.general_category.get(c)) & MARK_MASK) != 0
     }
 
+    /// Returns the number of characters in `label` whose General_Category
+    /// is Enclosing_Mark.
+    ///
+    /// Stacked enclosing marks can be abused for rendering tricks, so some
+    /// registries cap their number.
+    #[inline]
+    pub fn enclosing_mark_count(&self, label: &[char]) -> usize {
+        label
+            .iter()
+            .filter(|&&c| self.general_category.get(c) == GeneralCategory::EnclosingMark)
+            .count()
+    }
+
+    /// `true` iff `c` is U+200B ZERO WIDTH SPACE, U+200C ZERO WIDTH
+    /// NON-JOINER, U+200D ZERO WIDTH JOINER, U+2060 WORD JOINER, or U+FEFF
+    /// ZERO WIDTH NO-BREAK SPACE.
+    ///
+    /// These are flagged as spoofing vectors regardless of whether UTS 46
+    /// maps, ignores, or allows them.
+    #[inline(always)]
+    pub fn is_zero_width(&self, c: char) -> bool {
+        matches!(
+            c,
+            '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}'
+        )
+    }
+
+    /// `true` iff `c` has the Prepended_Concatenation_Mark property.
+    ///
+    /// These characters, such as the Arabic number signs, have the
+    /// General_Category Format, so they are not marks for the purpose of
+    /// [`is_mark`][Self::is_mark].
+    #[inline(always)]
+    pub fn is_prepended_concatenation_mark(&self, c: char) -> bool {
+        self.prepended_concatenation_mark.contains(c)
+    }
+
+    /// Returns the union of the General_Category values of the characters
+    /// of `label`.
+    #[inline]
+    pub fn label_category_mask(&self, label: &[char]) -> GeneralCategoryMask {
+        GeneralCategoryMask(label.iter().fold(0, |acc, &c| {
+            acc | general_category_to_mask(self.general_category.get(c))
+        }))
+    }
+
     /// Returns the Bidi_Class of `c`.
     #[inline(always)]
     pub fn bidi_class(&self, c: char) -> BidiClass {
+        if let Some(&bc) = BIDI_ASCII.get(c as usize) {
+            return BidiClass(icu_properties::BidiClass(bc));
+        }
         BidiClass(self.bidi_class.get(c))
     }
 
+    /// Returns every distinct Joining_Type value: C, D, R, L, T, and U.
+    #[inline(always)]
+    pub fn all_joining_types() -> &'static [JoiningType] {
+        &ALL_JOINING_TYPES
+    }
+
+    /// Returns every distinct Bidi_Class value.
+    #[inline(always)]
+    pub fn all_bidi_classes() -> &'static [BidiClass] {
+        &ALL_BIDI_CLASSES
+    }
+
+    /// Classifies `label` as LTR or RTL based on the Bidi_Class of its
+    /// first character, or as invalid for a bidi domain if the first
+    /// character is neither L, R, nor AL.
+    #[inline]
+    pub fn classify_label_direction(&self, label: &[char]) -> LabelDirection {
+        match label.first() {
+            Some(&c) => {
+                let bc = self.bidi_class(c);
+                if bc.is_ltr() {
+                    LabelDirection::Ltr
+                } else if bc.to_mask().intersects(FIRST_BC_MASK) {
+                    LabelDirection::Rtl
+                } else {
+                    LabelDirection::Invalid
+                }
+            }
+            None => LabelDirection::Invalid,
+        }
+    }
+
+    /// Determines in a single pass over `domain` both whether it is a bidi
+    /// domain and its base direction.
+    pub fn bidi_domain_info(&self, domain: &str) -> BidiDomainInfo {
+        let mut is_bidi = false;
+        let mut direction = LabelDirection::Invalid;
+        for c in domain.chars() {
+            let bc = self.bidi_class(c);
+            if direction == LabelDirection::Invalid {
+                if bc.is_ltr() {
+                    direction = LabelDirection::Ltr;
+                } else if bc.to_mask().intersects(FIRST_BC_MASK) {
+                    direction = LabelDirection::Rtl;
+                }
+            }
+            if bc.to_mask().intersects(RTL_MASK) {
+                is_bidi = true;
+                if direction != LabelDirection::Invalid {
+                    break;
+                }
+            }
+        }
+        BidiDomainInfo { is_bidi, direction }
+    }
+
+    /// Returns the Script of `c`.
+    #[inline(always)]
+    pub fn script(&self, c: char) -> Script {
+        Script(
+            self.script_with_extensions
+                .borrowed()
+                .get_script_val(u32::from(c)),
+        )
+    }
+
+    /// Returns the Script_Extensions of `c` augmented as in UTS 39: Common
+    /// and Inherited become the set of all scripts, Han adds Han with
+    /// Bopomofo, Japanese, and Korean, Hiragana and Katakana add Japanese,
+    /// Hangul adds Korean, and Bopomofo adds Han with Bopomofo.
+    fn augmented_script_extensions(&self, c: char) -> ScriptMask {
+        use icu_properties::Script as S;
+        let mut mask = ScriptMask::EMPTY;
+        for script in self
+            .script_with_extensions
+            .borrowed()
+            .get_script_extensions_val(u32::from(c))
+            .iter()
+        {
+            if script == S::Common || script == S::Inherited {
+                return ALL_SCRIPTS_MASK;
+            }
+            mask.insert(script);
+            if script == S::Han {
+                mask.insert(HAN_WITH_BOPOMOFO);
+                mask.insert(JAPANESE);
+                mask.insert(KOREAN);
+            } else if script == S::Hiragana || script == S::Katakana {
+                mask.insert(JAPANESE);
+            } else if script == S::Hangul {
+                mask.insert(KOREAN);
+            } else if script == S::Bopomofo {
+                mask.insert(HAN_WITH_BOPOMOFO);
+            }
+        }
+        mask
+    }
+
+    /// `true` iff `label` contains decimal digits (General_Category
+    /// Decimal_Number) from more than one set of digits, e.g. ASCII digits
+    /// mixed with Devanagari digits or with U+1D7CE..U+1D7D7 MATHEMATICAL
+    /// BOLD DIGITs.
+    ///
+    /// As in the mixed-number check of UTS 39, digit sets are told apart by
+    /// their digit zero, so sets of the same script count as different.
+    pub fn mixed_digit_scripts(&self, label: &[char]) -> bool {
+        let mut digit_zero = None;
+        for &c in label {
+            if self.general_category.get(c) != GeneralCategory::DecimalNumber {
+                continue;
+            }
+            let zero = self.digit_zero(c);
+            match digit_zero {
+                None => digit_zero = Some(zero),
+                Some(z) if z != zero => return true,
+                Some(_) => {}
+            }
+        }
+        false
+    }
+
+    /// Returns the code point of the digit zero of the set of decimal
+    /// digits that `c` belongs to.
+    ///
+    /// Unicode encodes decimal digits in contiguous runs of complete sets
+    /// from zero to nine, so the numeric value of `c` is the number of
+    /// decimal digits immediately before it modulo 10.
+    fn digit_zero(&self, c: char) -> u32 {
+        let preceding = (0..u32::from(c))
+            .rev()
+            .map_while(char::from_u32)
+            .take_while(|&p| self.general_category.get(p) == GeneralCategory::DecimalNumber)
+            .count();
+        u32::from(c) - (preceding % 10) as u32
+    }
+
+    /// Narrows the `running` set of scripts that a label can be written in
+    /// by the (UTS 39-augmented) Script_Extensions of `c`, or returns `None`
+    /// if no script remains, i.e. `c` isn't compatible with the characters
+    /// seen before.
+    ///
+    /// This is the inner loop of UTS 39 single-script resolution. Start with
+    /// [`ALL_SCRIPTS_MASK`] and feed each character of the label in turn.
+    #[inline]
+    pub fn scripts_compatible(&self, running: ScriptMask, c: char) -> Option<ScriptMask> {
+        let narrowed = running.intersection(self.augmented_script_extensions(c));
+        if narrowed == ScriptMask::EMPTY {
+            None
+        } else {
+            Some(narrowed)
+        }
+    }
+
+    /// Returns the single script that all the characters of `label` that
+    /// aren't Common or Inherited share via their (UTS 39-augmented)
+    /// Script_Extensions.
+    ///
+    /// If the shared set contains an augmenting script alongside exactly
+    /// one other script (e.g. a Han-only label), that other script is
+    /// returned. Otherwise, the result can be one of the augmenting scripts
+    /// [`Script::JAPANESE`], [`Script::KOREAN`], or
+    /// [`Script::HAN_WITH_BOPOMOFO`], which [`script`][Self::script] never
+    /// returns; e.g. a label mixing Han and Hiragana yields
+    /// `Script::JAPANESE`. Returns `None` if the label mixes scripts, if
+    /// more than one script remains possible, or if every character is
+    /// Common or Inherited.
+    pub fn dominant_script(&self, label: &[char]) -> Option<Script> {
+        let resolved = label.iter().fold(ALL_SCRIPTS_MASK, |acc, &c| {
+            acc.intersection(self.augmented_script_extensions(c))
+        });
+        if resolved == ALL_SCRIPTS_MASK {
+            return None;
+        }
+        let mut unaugmented = resolved;
+        unaugmented.remove(HAN_WITH_BOPOMOFO);
+        unaugmented.remove(JAPANESE);
+        unaugmented.remove(KOREAN);
+        unaugmented
+            .single()
+            .or_else(|| resolved.single())
+            .map(Script)
+    }
+
     /// Returns the Joining_Type of `c`.
     #[inline(always)]
     pub fn joining_type(&self, c: char) -> JoiningType {
         JoiningType(self.joining_type.get(c))
     }
 
+    /// Checks [`joining_type`][Self::joining_type] against the expected
+    /// values in `cases` and returns the first mismatch as a tuple of the
+    /// character, the expected value, and the actual value.
+    ///
+    /// This lets downstream test suites check the joining behavior of the
+    /// back end that the `Cargo.lock` pins.
+    pub fn verify_joining_types(
+        &self,
+        cases: &[(char, JoiningType)],
+    ) -> Result<(), (char, JoiningType, JoiningType)> {
+        for &(c, expected) in cases {
+            let actual = self.joining_type(c);
+            if actual != expected {
+                return Err((c, expected, actual));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of characters in `label` whose Joining_Type is
+    /// Transparent.
+    #[inline]
+    pub fn transparent_count(&self, label: &[char]) -> usize {
+        label
+            .iter()
+            .filter(|&&c| self.joining_type(c).is_transparent())
+            .count()
+    }
+
+    /// Returns the union of the Joining_Type values of the characters of
+    /// `label` excluding Transparent, which ContextJ skips over.
+    #[inline]
+    pub fn label_joining_mask_no_transparent(&self, label: &[char]) -> JoiningTypeMask {
+        JoiningTypeMask(
+            label
+                .iter()
+                .map(|&c| self.joining_type(c))
+                .filter(|jt| !jt.is_transparent())
+                .fold(0, |acc, jt| acc | jt.to_mask().0),
+        )
+    }
+
     /// See the [method of the same name in `icu_normalizer`][1] for the
     /// exact semantics.
     ///
+    /// Empty input yields empty output. Mapping doesn't remove label
+    /// separators, so an empty label in a domain (as in `"a..b"`) survives
+    /// mapping and has to be rejected by the caller if needed.
+    ///
     /// [1]: https://docs.rs/icu_normalizer/latest/icu_normalizer/uts46/struct.Uts46Mapper.html#method.map_normalize
     #[inline(always)]
     pub fn map_normalize<'delegate, I: Iterator<Item = char> + 'delegate>(
@@ -268,9 +1446,221 @@ impl Adapter {
         self.mapper.map_normalize(iter)
     }
 
+    /// Like [`map_normalize`][Self::map_normalize] but with transitional
+    /// processing of the deviation characters: U+00DF and U+1E9E map to
+    /// "ss", U+03C2 maps to U+03C3, and U+200C and U+200D are removed.
+    ///
+    /// This is for interoperability with IDNA2003-era software only.
+    /// Nontransitional processing remains the default.
+    pub fn map_normalize_transitional<'delegate, I: Iterator<Item = char> + 'delegate>(
+        &'delegate self,
+        iter: I,
+    ) -> impl Iterator<Item = char> + 'delegate {
+        // The deviation characters are replaced before the mapping so that
+        // the result is normalized, e.g. the last "s" from U+00DF composes
+        // with a following acute accent. U+1E9E is the only character that
+        // maps to U+00DF, so it is replaced here, too.
+        self.mapper.map_normalize(iter.flat_map(|c| {
+            let (first, second) = match c {
+                '\u{200C}' | '\u{200D}' => (None, None),
+                '\u{03C2}' => (Some('\u{03C3}'), None),
+                '\u{00DF}' | '\u{1E9E}' => (Some('s'), Some('s')),
+                c => (Some(c), None),
+            };
+            first.into_iter().chain(second)
+        }))
+    }
+
+    /// Performs the same mapping as [`map_normalize`][Self::map_normalize]
+    /// on a string and returns the result together with its 64-bit FNV-1a
+    /// hash, computed over the UTF-8 bytes of the result in the same pass.
+    ///
+    /// FNV-1a is used because it is simple and fully specified, so the
+    /// hash is stable across platforms and versions of this crate and can
+    /// be used for persistent cache keys. It is not resistant to collision
+    /// attacks.
+    pub fn map_normalize_hash(&self, input: &str) -> (String, u64) {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+        let mut output = String::with_capacity(input.len());
+        let mut hash = FNV_OFFSET_BASIS;
+        for c in self.mapper.map_normalize(input.chars()) {
+            let mut buf = [0u8; 4];
+            for &b in c.encode_utf8(&mut buf).as_bytes() {
+                hash ^= u64::from(b);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+            output.push(c);
+        }
+        (output, hash)
+    }
+
+    /// Checks that [`map_normalize`][Self::map_normalize] is idempotent for
+    /// `input` (mapping the output again doesn't change it) and that its
+    /// output is in NFC.
+    ///
+    /// This is meant as a reusable oracle for fuzzing and tests.
+    pub fn assert_mapping_invariants(&self, input: &str) -> Result<(), InvariantViolation> {
+        let mapped: String = self.mapper.map_normalize(input.chars()).collect();
+        if self.mapper.map_normalize(mapped.chars()).ne(mapped.chars()) {
+            return Err(InvariantViolation::NotIdempotent);
+        }
+        if !self.is_nfc(&mapped) {
+            return Err(InvariantViolation::NotNfc);
+        }
+        Ok(())
+    }
+
+    /// Maps `input` like [`map_normalize`][Self::map_normalize] unless
+    /// `input` contains a disallowed character, in which case `None` is
+    /// returned without allocating.
+    ///
+    /// The characters that the STD3 rules disallow, including those that
+    /// map to such ASCII characters, count as disallowed, as with
+    /// [`is_uts46_valid`][Self::is_uts46_valid].
+    pub fn map_normalize_checked(&self, input: &str) -> Option<String> {
+        if input
+            .chars()
+            .any(|c| self.uts46_status(c) == Uts46Status::Disallowed)
+        {
+            return None;
+        }
+        if self.is_ascii(input) {
+            return Some(input.to_ascii_lowercase());
+        }
+        Some(self.mapper.map_normalize(input.chars()).collect())
+    }
+
+    /// Maps `input` like [`map_normalize`][Self::map_normalize] unless
+    /// `input` contains a deviation character (U+00DF, U+03C2, U+200C, or
+    /// U+200D) or a character that maps to one (such as U+1E9E LATIN
+    /// CAPITAL LETTER SHARP S), in which case the first one is reported as
+    /// an error.
+    ///
+    /// This makes the caller choose between transitional and
+    /// nontransitional processing explicitly instead of silently getting
+    /// the nontransitional treatment.
+    pub fn map_normalize_strict_deviation(
+        &self,
+        input: &str,
+    ) -> Result<String, DeviationAmbiguity> {
+        if let Some((offset, character)) =
+            input
+                .char_indices()
+                .find(|&(_, c)| match self.uts46_status(c) {
+                    Uts46Status::Deviation => true,
+                    Uts46Status::Mapped => self
+                        .mapper
+                        .map_normalize(core::iter::once(c))
+                        .any(is_deviation),
+                    _ => false,
+                })
+        {
+            return Err(DeviationAmbiguity { character, offset });
+        }
+        Ok(self.mapper.map_normalize(input.chars()).collect())
+    }
+
+    /// Maps a whole domain like [`map_normalize`][Self::map_normalize] and
+    /// returns the result as a string.
+    ///
+    /// The four label separators are passed through as U+002E FULL STOP, so
+    /// the result can be split into labels at `'.'`. Empty labels are
+    /// preserved.
+    pub fn map_normalize_keep_separators(&self, domain: &str) -> String {
+        self.mapper
+            .map_normalize(
+                domain
+                    .chars()
+                    .map(|c| if is_label_separator(c) { '.' } else { c }),
+            )
+            .collect()
+    }
+
+    /// Returns the UTF-8 length in bytes of
+    /// [`map_normalize_keep_separators`][Self::map_normalize_keep_separators]
+    /// applied to `domain`, i.e. of all the mapped labels plus one byte per
+    /// separator, without allocating the mapped domain.
+    pub fn domain_mapped_length(&self, domain: &str) -> usize {
+        if self.is_ascii(domain) {
+            // ASCII maps to ASCII one character at a time.
+            return domain.len();
+        }
+        self.mapper
+            .map_normalize(
+                domain
+                    .chars()
+                    .map(|c| if is_label_separator(c) { '.' } else { c }),
+            )
+            .map(char::len_utf8)
+            .sum()
+    }
+
+    /// Maps `input` like [`map_normalize`][Self::map_normalize] into `out`,
+    /// which is cleared first, so that the capacity of `out` can be reused
+    /// across calls.
+    pub fn map_normalize_into_chars(&self, input: &str, out: &mut Vec<char>) {
+        out.clear();
+        out.extend(self.mapper.map_normalize(input.chars()));
+    }
+
+    /// Maps `input` like [`map_normalize`][Self::map_normalize] and returns
+    /// the result in the normalization form `form`.
+    ///
+    /// With [`NormalizationForm::Nfc`], the result is the same as
+    /// collecting the output of `map_normalize`. The normalizers for the
+    /// other forms use the same data source as the rest of the adapter.
+    pub fn map_normalize_form(&self, input: &str, form: NormalizationForm) -> String {
+        let mapped = self.mapper.map_normalize(input.chars());
+        match (form, &self.form_normalizers) {
+            (NormalizationForm::Nfc, _) => mapped.collect(),
+            #[cfg(feature = "compiled_data")]
+            (NormalizationForm::Nfd, FormNormalizers::Compiled) => DecomposingNormalizer::new_nfd()
+                .normalize_iter(mapped)
+                .collect(),
+            #[cfg(feature = "compiled_data")]
+            (NormalizationForm::Nfkc, FormNormalizers::Compiled) => ComposingNormalizer::new_nfkc()
+                .normalize_iter(mapped)
+                .collect(),
+            #[cfg(feature = "compiled_data")]
+            (NormalizationForm::Nfkd, FormNormalizers::Compiled) => {
+                DecomposingNormalizer::new_nfkd()
+                    .normalize_iter(mapped)
+                    .collect()
+            }
+            (NormalizationForm::Nfd, FormNormalizers::Loaded(loaded)) => {
+                loaded.nfd.normalize_iter(mapped).collect()
+            }
+            (NormalizationForm::Nfkc, FormNormalizers::Loaded(loaded)) => {
+                loaded.nfkc.normalize_iter(mapped).collect()
+            }
+            (NormalizationForm::Nfkd, FormNormalizers::Loaded(loaded)) => {
+                loaded.nfkd.normalize_iter(mapped).collect()
+            }
+        }
+    }
+
+    /// Like [`map_normalize`][Self::map_normalize] but yields each output
+    /// character together with its index in the output, counting in
+    /// characters from zero.
+    #[inline(always)]
+    pub fn map_normalize_enumerated<'delegate, I: Iterator<Item = char> + 'delegate>(
+        &'delegate self,
+        iter: I,
+    ) -> impl Iterator<Item = (usize, char)> + 'delegate {
+        self.mapper.map_normalize(iter).enumerate()
+    }
+
     /// See the [method of the same name in `icu_normalizer`][1] for the
     /// exact semantics.
     ///
+    /// Exactly one U+FFFD is emitted for each disallowed (or, here, ignored)
+    /// input character, regardless of the character's UTF-8 length and of
+    /// whether disallowed characters are adjacent.
+    ///
+    /// The four label separators are never replaced with U+FFFD: they are
+    /// all mapped to U+002E FULL STOP.
+    ///
     /// [1]: https://docs.rs/icu_normalizer/latest/icu_normalizer/uts46/struct.Uts46Mapper.html#method.normalize_validate
     #[inline(always)]
     pub fn normalize_validate<'delegate, I: Iterator<Item = char> + 'delegate>(
@@ -279,4 +1669,455 @@ impl Adapter {
     ) -> impl Iterator<Item = char> + 'delegate {
         self.mapper.normalize_validate(iter)
     }
+
+    /// Performs the same mapping as [`map_normalize`][Self::map_normalize]
+    /// on a string, borrowing `input` if the mapping wouldn't change it.
+    ///
+    /// ASCII input is handled without going through the mapping pipeline.
+    /// Other input is first compared lazily with the output of the mapping,
+    /// which stops at the first change (or right away if `input` contains
+    /// an uppercase ASCII letter), so input that needs mapping is partly
+    /// processed twice.
+    pub fn map_normalize_str<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        if let Some(ascii) = self.ascii_str(input) {
+            return ascii;
+        }
+        if has_ascii_uppercase(input) || self.mapper.map_normalize(input.chars()).ne(input.chars())
+        {
+            return Cow::Owned(self.mapper.map_normalize(input.chars()).collect());
+        }
+        Cow::Borrowed(input)
+    }
+
+    /// Performs the same mapping as
+    /// [`normalize_validate`][Self::normalize_validate] on a string,
+    /// borrowing `input` if the mapping wouldn't change it.
+    ///
+    /// The cost is as with [`map_normalize_str`][Self::map_normalize_str].
+    pub fn normalize_validate_str<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        if let Some(ascii) = self.ascii_str(input) {
+            return ascii;
+        }
+        if has_ascii_uppercase(input)
+            || self
+                .mapper
+                .normalize_validate(input.chars())
+                .ne(input.chars())
+        {
+            return Cow::Owned(self.mapper.normalize_validate(input.chars()).collect());
+        }
+        Cow::Borrowed(input)
+    }
+
+    /// The result of both [`map_normalize_str`][Self::map_normalize_str]
+    /// and [`normalize_validate_str`][Self::normalize_validate_str] if
+    /// `input` is ASCII, which both mappings only lowercase.
+    fn ascii_str<'a>(&self, input: &'a str) -> Option<Cow<'a, str>> {
+        if !self.is_ascii(input) {
+            return None;
+        }
+        Some(if has_ascii_uppercase(input) {
+            Cow::Owned(input.to_ascii_lowercase())
+        } else {
+            Cow::Borrowed(input)
+        })
+    }
+
+    /// Returns the number of U+FFFD characters that
+    /// [`normalize_validate`][Self::normalize_validate] emits for `input`,
+    /// i.e. the number of disallowed or ignored characters in `input`.
+    #[inline]
+    pub fn count_replacements(&self, input: &str) -> usize {
+        self.mapper
+            .normalize_validate(input.chars())
+            .filter(|&c| c == '\u{FFFD}')
+            .count()
+    }
+
+    /// Splits `domain` into labels at the label separators and returns
+    /// each label after [`map_normalize`][Self::map_normalize] together
+    /// with the byte range of the unmapped label in `domain`.
+    ///
+    /// The split happens before mapping, so the ranges point into the
+    /// original input and never include a separator. Empty labels are
+    /// returned as empty strings with empty ranges so that the caller can
+    /// reject them; an empty `domain` yields a single empty label.
+    pub fn labels_with_spans(&self, domain: &str) -> Vec<(Range<usize>, String)> {
+        let mut labels = Vec::new();
+        let mut start = 0;
+        for (i, c) in domain.char_indices() {
+            if is_label_separator(c) {
+                labels.push((
+                    start..i,
+                    self.mapper
+                        .map_normalize(domain[start..i].chars())
+                        .collect(),
+                ));
+                start = i + c.len_utf8();
+            }
+        }
+        labels.push((
+            start..domain.len(),
+            self.mapper.map_normalize(domain[start..].chars()).collect(),
+        ));
+        labels
+    }
+
+    /// Returns the UTS 46 status of `c` with UseSTD3ASCIIRules=true.
+    ///
+    /// Characters whose mapping contains a character that the STD3 rules
+    /// disallow (e.g. U+FF3F FULLWIDTH LOW LINE, which maps to `_`) are
+    /// disallowed, and so are the _disallowed_STD3_valid_ characters.
+    fn uts46_status(&self, c: char) -> Uts46Status {
+        if c.is_ascii_uppercase() {
+            return Uts46Status::Mapped;
+        }
+        if is_std3_disallowed(c) {
+            return Uts46Status::Disallowed;
+        }
+        if c.is_ascii() {
+            return Uts46Status::Valid;
+        }
+        if is_deviation(c) {
+            return Uts46Status::Deviation;
+        }
+        let mut len = 0;
+        let mut identical = true;
+        for m in self.mapper.map_normalize(core::iter::once(c)) {
+            if m == '\u{FFFD}' || is_std3_disallowed(m) {
+                return Uts46Status::Disallowed;
+            }
+            identical &= m == c;
+            len += 1;
+        }
+        match len {
+            0 => Uts46Status::Ignored,
+            1 if identical => Uts46Status::Valid,
+            _ => Uts46Status::Mapped,
+        }
+    }
+
+    /// `true` iff the UTS 46 status of `c` is _valid_, i.e. not _mapped_,
+    /// _ignored_, _deviation_, or _disallowed_.
+    ///
+    /// The status is that with UseSTD3ASCIIRules=true, so the only valid
+    /// ASCII characters are lowercase letters, digits, the hyphen, and the
+    /// full stop, and U+2260, U+226E, and U+226F as well as characters that
+    /// map to other ASCII characters (e.g. U+FF3F FULLWIDTH LOW LINE) are
+    /// not valid.
+    #[inline]
+    pub fn is_uts46_valid(&self, c: char) -> bool {
+        self.uts46_status(c) == Uts46Status::Valid
+    }
+
+    /// Performs the same mapping as [`map_normalize`][Self::map_normalize]
+    /// on a string and additionally counts the input characters by their
+    /// UTS 46 status.
+    ///
+    /// The statuses are those with UseSTD3ASCIIRules=true, so characters
+    /// that are or map to ASCII characters that the STD3 rules disallow
+    /// count as disallowed even though the mapping passes them through.
+    pub fn map_normalize_with_stats(&self, input: &str) -> (String, MappingStats) {
+        let mut stats = MappingStats::default();
+        let output = self
+            .mapper
+            .map_normalize(input.chars().inspect(|&c| match self.uts46_status(c) {
+                Uts46Status::Valid => {}
+                Uts46Status::Mapped => stats.mapped += 1,
+                Uts46Status::Ignored => stats.ignored += 1,
+                Uts46Status::Deviation => stats.deviation += 1,
+                Uts46Status::Disallowed => stats.disallowed += 1,
+            }))
+            .collect();
+        (output, stats)
+    }
+
+    /// `true` iff [`map_normalize`][Self::map_normalize] maps `label` to
+    /// the empty string, e.g. because `label` consists only of ignored
+    /// characters such as U+00AD SOFT HYPHEN.
+    #[inline]
+    pub fn maps_to_empty(&self, label: &str) -> bool {
+        if self.is_ascii(label) {
+            return label.is_empty();
+        }
+        self.mapper.map_normalize(label.chars()).next().is_none()
+    }
+
+    /// `true` iff every character of `label` after
+    /// [`map_normalize`][Self::map_normalize] is in the ASCII
+    /// letter-digit-hyphen repertoire, i.e. the mapped label needs no
+    /// Punycode encoding and passes the STD3 ASCII rules.
+    #[inline]
+    pub fn to_ascii_repertoire_ok(&self, label: &str) -> bool {
+        if self.is_ascii(label) {
+            // The mapping only lowercases ASCII, which doesn't affect the
+            // check.
+            return label
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-');
+        }
+        self.mapper
+            .map_normalize(label.chars())
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    }
+
+    /// Performs the same mapping as [`map_normalize`][Self::map_normalize]
+    /// on a string and additionally returns spans that map byte ranges of
+    /// the output back to byte ranges of the input.
+    ///
+    /// Each span is a pair of an output range and an input range. The spans
+    /// are in order, are contiguous, and together cover both the whole
+    /// output and the whole input. Since normalization can combine
+    /// characters, a span covers the smallest run of input characters that
+    /// maps independently of its neighbors. Ignored characters are attributed
+    /// to the preceding span or, at the start of the input, get a span with
+    /// an empty output range.
+    #[allow(clippy::type_complexity)]
+    pub fn map_normalize_with_spans(
+        &self,
+        input: &str,
+    ) -> (String, Vec<(Range<usize>, Range<usize>)>) {
+        let mut output = String::with_capacity(input.len());
+        let mut spans = Vec::new();
+        let mut segment_start = 0;
+        for (i, c) in input.char_indices().skip(1) {
+            // A new segment can only start at a character whose mapping
+            // starts with a starter. Even then, the starter could combine
+            // with the preceding segment, so check that mapping the two
+            // parts separately gives the same result as mapping them
+            // together.
+            let starts_with_starter = self
+                .mapper
+                .map_normalize(core::iter::once(c))
+                .next()
+                .map_or(false, |first| {
+                    self.canonical_combining_class.get(first)
+                        == CanonicalCombiningClass::NotReordered
+                });
+            if !starts_with_starter {
+                continue;
+            }
+            let segment = &input[segment_start..i];
+            let with_c = &input[segment_start..i + c.len_utf8()];
+            if self
+                .mapper
+                .map_normalize(segment.chars())
+                .chain(self.mapper.map_normalize(core::iter::once(c)))
+                .ne(self.mapper.map_normalize(with_c.chars()))
+            {
+                continue;
+            }
+            let output_start = output.len();
+            output.extend(self.mapper.map_normalize(segment.chars()));
+            spans.push((output_start..output.len(), segment_start..i));
+            segment_start = i;
+        }
+        if segment_start < input.len() {
+            let output_start = output.len();
+            output.extend(self.mapper.map_normalize(input[segment_start..].chars()));
+            spans.push((output_start..output.len(), segment_start..input.len()));
+        }
+        (output, spans)
+    }
+
+    /// `true` iff `input` is in Normalization Form C.
+    ///
+    /// Unlike the NFC_Quick_Check property, this check is definitive: the
+    /// input is compared with its normalized form, which is computed lazily
+    /// so that the comparison stops at the first difference.
+    #[inline]
+    pub fn is_nfc(&self, input: &str) -> bool {
+        if self.is_ascii(input) {
+            return true;
+        }
+        self.nfc.normalize_iter(input.chars()).eq(input.chars())
+    }
+
+    /// `true` iff `input` is entirely ASCII.
+    ///
+    /// This is the single decision point for the ASCII fast paths of this
+    /// crate, so that a faster check only needs to be added here.
+    #[inline(always)]
+    pub fn is_ascii(&self, input: &str) -> bool {
+        input.is_ascii()
+    }
+
+    /// `true` iff `label` satisfies rules 1 to 6 of the bidi rule of
+    /// RFC 5893, which apply to every label of a bidi domain.
+    fn bidi_rule_ok(&self, label: &[char]) -> bool {
+        if label.is_empty() {
+            return true;
+        }
+        let (middle_mask, last_mask, rtl) = match self.classify_label_direction(label) {
+            LabelDirection::Ltr => (MIDDLE_LTR_MASK, LAST_LTR_MASK, false),
+            LabelDirection::Rtl => (MIDDLE_RTL_MASK, LAST_RTL_MASK, true),
+            LabelDirection::Invalid => return false,
+        };
+        let mut has_european_number = false;
+        let mut has_arabic_number = false;
+        for &c in label {
+            let bc = self.bidi_class(c);
+            if !bc.to_mask().intersects(middle_mask) {
+                return false;
+            }
+            has_european_number |= bc.is_european_number();
+            has_arabic_number |= bc.is_arabic_number();
+        }
+        if rtl && has_european_number && has_arabic_number {
+            return false;
+        }
+        label
+            .iter()
+            .rev()
+            .map(|&c| self.bidi_class(c))
+            .find(|bc| !bc.is_nonspacing_mark())
+            .map_or(false, |bc| bc.to_mask().intersects(last_mask))
+    }
+
+    /// `true` iff every U+200C ZERO WIDTH NON-JOINER and U+200D ZERO WIDTH
+    /// JOINER in `label` satisfies its CONTEXTJ rule of RFC 5892.
+    ///
+    /// Either character is allowed right after a virama. Otherwise, ZWJ is
+    /// rejected and ZWNJ requires a left-joining or dual-joining character
+    /// before it and a right-joining or dual-joining character after it,
+    /// skipping over transparent characters. Returns `false` on the first
+    /// violation.
+    pub fn contextj_ok(&self, label: &[char]) -> bool {
+        for (i, &c) in label.iter().enumerate() {
+            if c != '\u{200C}' && c != '\u{200D}' {
+                continue;
+            }
+            if i > 0 && self.is_virama(label[i - 1]) {
+                continue;
+            }
+            if c == '\u{200D}' {
+                return false;
+            }
+            let joins_left = label[..i]
+                .iter()
+                .rev()
+                .map(|&b| self.joining_type(b))
+                .find(|jt| !jt.is_transparent())
+                .map_or(false, |jt| {
+                    jt.to_mask().intersects(LEFT_OR_DUAL_JOINING_MASK)
+                });
+            let joins_right = label[i + 1..]
+                .iter()
+                .map(|&a| self.joining_type(a))
+                .find(|jt| !jt.is_transparent())
+                .map_or(false, |jt| {
+                    jt.to_mask().intersects(RIGHT_OR_DUAL_JOINING_MASK)
+                });
+            if !(joins_left && joins_right) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// `true` iff every character in `label` that has a CONTEXTO rule in
+    /// RFC 5892 satisfies it.
+    fn contexto_ok(&self, label: &[char]) -> bool {
+        use icu_properties::Script as S;
+        for (i, &c) in label.iter().enumerate() {
+            let before = i.checked_sub(1).map(|b| label[b]);
+            let after = label.get(i + 1).copied();
+            let ok = match c {
+                // MIDDLE DOT
+                '\u{00B7}' => before == Some('l') && after == Some('l'),
+                // GREEK LOWER NUMERAL SIGN (KERAIA)
+                '\u{0375}' => after.map_or(false, |a| self.script(a).0 == S::Greek),
+                // HEBREW PUNCTUATION GERESH and GERSHAYIM
+                '\u{05F3}' | '\u{05F4}' => before.map_or(false, |b| self.script(b).0 == S::Hebrew),
+                // KATAKANA MIDDLE DOT
+                '\u{30FB}' => label.iter().any(|&o| {
+                    let script = self.script(o).0;
+                    script == S::Hiragana || script == S::Katakana || script == S::Han
+                }),
+                // ARABIC-INDIC DIGITS
+                '\u{0660}'..='\u{0669}' => {
+                    !label.iter().any(|o| ('\u{06F0}'..='\u{06F9}').contains(o))
+                }
+                // EXTENDED ARABIC-INDIC DIGITS
+                '\u{06F0}'..='\u{06F9}' => {
+                    !label.iter().any(|o| ('\u{0660}'..='\u{0669}').contains(o))
+                }
+                _ => true,
+            };
+            if !ok {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Maps `domain` like
+    /// [`map_normalize_keep_separators`][Self::map_normalize_keep_separators]
+    /// and reports for each resulting label whether it is free of
+    /// disallowed characters (with UseSTD3ASCIIRules=true), whether it passes the bidi rule of RFC 5893
+    /// (applicable if the domain is a bidi domain), and whether it passes
+    /// the CONTEXTJ and CONTEXTO rules of RFC 5892.
+    ///
+    /// Labels in the Punycode form are not decoded, and the other validity
+    /// criteria of UTS 46 (such as hyphen placement) are not checked.
+    pub fn validate_labels(&self, domain: &str) -> Vec<LabelValidity> {
+        let mapped = self.map_normalize_keep_separators(domain);
+        let bidi_domain = self.bidi_domain_info(&mapped).is_bidi();
+        let mut label = Vec::new();
+        mapped
+            .split('.')
+            .map(|l| {
+                label.clear();
+                label.extend(l.chars());
+                LabelValidity {
+                    mapping_ok: !label
+                        .iter()
+                        .any(|&c| c == '\u{FFFD}' || is_std3_disallowed(c)),
+                    bidi_ok: !bidi_domain || self.bidi_rule_ok(&label),
+                    context_ok: self.contextj_ok(&label) && self.contexto_ok(&label),
+                }
+            })
+            .collect()
+    }
 }
+
+/// Returns the Bidi_Class of `c` using data compiled into the binary.
+///
+/// See [`Adapter::bidi_class`].
+#[cfg(feature = "compiled_data")]
+#[inline(always)]
+pub fn bidi_class(c: char) -> BidiClass {
+    BidiClass(icu_properties::maps::bidi_class().get(c))
+}
+
+/// Returns the Joining_Type of `c` using data compiled into the binary.
+///
+/// See [`Adapter::joining_type`].
+#[cfg(feature = "compiled_data")]
+#[inline(always)]
+pub fn joining_type(c: char) -> JoiningType {
+    JoiningType(icu_properties::maps::joining_type().get(c))
+}
+
+/// `true` iff the Canonical_Combining_Class of `c` is Virama using data
+/// compiled into the binary.
+///
+/// See [`Adapter::is_virama`].
+#[cfg(feature = "compiled_data")]
+#[inline(always)]
+pub fn is_virama(c: char) -> bool {
+    CanonicalCombiningClassMap::new().get(c) == CanonicalCombiningClass::Virama
+}
+
+/// `true` iff the General_Category of `c` is Mark using data compiled into
+/// the binary.
+///
+/// See [`Adapter::is_mark`].
+#[cfg(feature = "compiled_data")]
+#[inline(always)]
+pub fn is_mark(c: char) -> bool {
+    (general_category_to_mask(icu_properties::maps::general_category().get(c)) & MARK_MASK) != 0
+}
+
+#[cfg(all(test, feature = "compiled_data"))]
+mod tests;
@@ -0,0 +1,1028 @@
+// Copyright The rust-url developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+
+#[test]
+fn joining_type_arabic_script_family() {
+    let adapter = Adapter::new();
+    assert_eq!(
+        adapter.verify_joining_types(&[
+            // Syriac
+            ('\u{0710}', JoiningType::RIGHT_JOINING), // ALAPH
+            ('\u{0712}', JoiningType::DUAL_JOINING),  // BETH
+            ('\u{0715}', JoiningType::RIGHT_JOINING), // DALATH
+            ('\u{0717}', JoiningType::RIGHT_JOINING), // HE
+            ('\u{071A}', JoiningType::DUAL_JOINING),  // HETH
+            // N'Ko
+            ('\u{07CA}', JoiningType::DUAL_JOINING), // A
+            ('\u{07D3}', JoiningType::DUAL_JOINING), // DA
+            ('\u{07FA}', JoiningType::JOIN_CAUSING), // LAJANYALAN
+            ('\u{07EB}', JoiningType::TRANSPARENT),  // SHORT HIGH TONE
+            // Mandaic
+            ('\u{0840}', JoiningType::RIGHT_JOINING), // HALQA
+            ('\u{0841}', JoiningType::DUAL_JOINING),  // AB
+            ('\u{0847}', JoiningType::RIGHT_JOINING), // IT
+            // Adlam
+            ('\u{1E900}', JoiningType::DUAL_JOINING), // CAPITAL ALIF
+            ('\u{1E922}', JoiningType::DUAL_JOINING), // SMALL ALIF
+            ('\u{1E944}', JoiningType::TRANSPARENT),  // ALIF LENGTHENER
+        ]),
+        Ok(())
+    );
+}
+
+#[test]
+fn bidi_class_recent_rtl_scripts() {
+    let adapter = Adapter::new();
+    for c in [
+        '\u{10D00}', // HANIFI ROHINGYA LETTER A
+        '\u{10D0A}', // HANIFI ROHINGYA LETTER TA
+        '\u{10E80}', // YEZIDI LETTER ELIF
+        '\u{10E90}', // YEZIDI LETTER YOT
+        '\u{10F70}', // OLD UYGHUR LETTER ALEPH
+        '\u{10F7A}', // OLD UYGHUR LETTER NUN
+    ] {
+        assert!(
+            adapter.bidi_class(c).to_mask().intersects(RTL_MASK),
+            "U+{:04X}",
+            u32::from(c)
+        );
+    }
+}
+
+#[test]
+fn map_normalize_with_spans_expansion() {
+    let adapter = Adapter::new();
+    // U+FB01 LATIN SMALL LIGATURE FI expands to two characters.
+    let (output, spans) = adapter.map_normalize_with_spans("a\u{FB01}B");
+    assert_eq!(output, "afib");
+    assert_eq!(spans, [(0..1, 0..1), (1..3, 1..4), (3..4, 4..5)]);
+}
+
+#[test]
+fn is_nfc() {
+    let adapter = Adapter::new();
+    assert!(adapter.is_nfc("caf\u{E9}"));
+    assert!(!adapter.is_nfc("cafe\u{301}"));
+    // U+0301 has NFC_Quick_Check=Maybe, but there's no precomposed x with
+    // acute.
+    assert!(adapter.is_nfc("x\u{301}"));
+}
+
+#[test]
+fn label_joining_mask_no_transparent() {
+    let adapter = Adapter::new();
+    // BEH (D) with FATHA and DAMMA (T)
+    let mask = adapter.label_joining_mask_no_transparent(&['\u{0628}', '\u{064E}', '\u{064F}']);
+    assert!(mask.intersects(JoiningType::DUAL_JOINING.to_mask()));
+    assert!(!mask.intersects(JoiningType::TRANSPARENT.to_mask()));
+    let mask = adapter.label_joining_mask_no_transparent(&['\u{064E}']);
+    assert!(!mask.intersects(JoiningType::TRANSPARENT.to_mask()));
+}
+
+#[test]
+fn label_category_mask() {
+    let adapter = Adapter::new();
+    let mask = adapter.label_category_mask(&['a', '+']);
+    assert!(mask.intersects(LETTER_MASK));
+    assert!(mask.intersects(SYMBOL_MASK));
+    assert!(!mask.intersects(PUNCTUATION_MASK));
+}
+
+#[test]
+fn prepended_concatenation_mark() {
+    let adapter = Adapter::new();
+    // ARABIC NUMBER SIGN
+    assert!(adapter.is_prepended_concatenation_mark('\u{0600}'));
+    assert!(!adapter.is_mark('\u{0600}'));
+    // COMBINING ACUTE ACCENT
+    assert!(!adapter.is_prepended_concatenation_mark('\u{0301}'));
+    assert!(adapter.is_mark('\u{0301}'));
+}
+
+#[test]
+fn map_normalize_enumerated() {
+    let adapter = Adapter::new();
+    let enumerated: Vec<(usize, char)> = adapter
+        .map_normalize_enumerated("Stra\u{DF}e\u{AD}.\u{FB01}".chars())
+        .collect();
+    let mapped: Vec<char> = adapter
+        .map_normalize("Stra\u{DF}e\u{AD}.\u{FB01}".chars())
+        .collect();
+    assert_eq!(enumerated.len(), mapped.len());
+    for (expected, &(i, c)) in enumerated.iter().enumerate() {
+        assert_eq!(i, expected);
+        assert_eq!(c, mapped[i]);
+    }
+}
+
+#[test]
+fn to_ascii_repertoire_ok() {
+    let adapter = Adapter::new();
+    assert!(adapter.to_ascii_repertoire_ok("example"));
+    assert!(adapter.to_ascii_repertoire_ok("Ex-Ample"));
+    assert!(!adapter.to_ascii_repertoire_ok("ex ample"));
+    assert!(!adapter.to_ascii_repertoire_ok("ex\u{E4}mple"));
+}
+
+#[test]
+fn full_composition_exclusion() {
+    let adapter = Adapter::new();
+    // DEVANAGARI LETTER QA is in CompositionExclusions.txt.
+    assert!(adapter.is_full_composition_exclusion('\u{0958}'));
+    // COMBINING GRAVE TONE MARK is a singleton decomposition.
+    assert!(adapter.is_full_composition_exclusion('\u{0340}'));
+    assert!(!adapter.is_full_composition_exclusion('\u{E9}'));
+}
+
+#[test]
+fn maps_to_empty() {
+    let adapter = Adapter::new();
+    assert!(adapter.maps_to_empty("\u{AD}"));
+    assert!(adapter.maps_to_empty("\u{AD}\u{200B}"));
+    assert!(adapter.maps_to_empty(""));
+    assert!(!adapter.maps_to_empty("a"));
+    assert!(!adapter.maps_to_empty("\u{AD}a"));
+}
+
+#[test]
+fn bidi_ascii_table() {
+    let bidi_class = icu_properties::maps::bidi_class();
+    for (c, &bc) in ('\0'..='\u{7F}').zip(BIDI_ASCII.iter()) {
+        assert_eq!(
+            icu_properties::BidiClass(bc),
+            bidi_class.get(c),
+            "U+{:04X}",
+            u32::from(c)
+        );
+    }
+}
+
+#[test]
+fn labels_with_spans() {
+    let adapter = Adapter::new();
+    // U+3002 IDEOGRAPHIC FULL STOP is three bytes long.
+    assert_eq!(
+        adapter.labels_with_spans("A\u{3002}b"),
+        [(0..1, String::from("a")), (4..5, String::from("b"))]
+    );
+    assert_eq!(
+        adapter.labels_with_spans("a..b"),
+        [
+            (0..1, String::from("a")),
+            (2..2, String::new()),
+            (3..4, String::from("b"))
+        ]
+    );
+}
+
+#[test]
+fn any_virama() {
+    let adapter = Adapter::new();
+    // KA, VIRAMA, SSA
+    assert!(adapter.any_virama(&['\u{0915}', '\u{094D}', '\u{0937}']));
+    assert!(!adapter.any_virama(&['a', 'b', 'c']));
+}
+
+#[test]
+fn replacement_per_disallowed_character() {
+    let adapter = Adapter::new();
+    // Two adjacent disallowed characters of different UTF-8 lengths.
+    let input = "a\u{80}\u{10FFFF}b";
+    assert_eq!(adapter.count_replacements(input), 2);
+    assert!(adapter
+        .normalize_validate(input.chars())
+        .eq("a\u{FFFD}\u{FFFD}b".chars()));
+    assert_eq!(adapter.count_replacements("example"), 0);
+}
+
+#[test]
+fn dominant_script() {
+    let adapter = Adapter::new();
+    let label: Vec<char> = "\u{65E5}\u{672C}\u{3054}".chars().collect();
+    assert_eq!(adapter.dominant_script(&label), Some(Script::JAPANESE));
+    assert_eq!(adapter.dominant_script(&['\u{65E5}']), Some(Script::HAN));
+    assert_eq!(
+        adapter.dominant_script(&['a', '1', 'b']),
+        Some(Script::LATIN)
+    );
+    // Latin a and Cyrillic a
+    assert_eq!(adapter.dominant_script(&['a', '\u{0430}']), None);
+    assert_eq!(adapter.dominant_script(&['1', '-']), None);
+    assert_eq!(adapter.script('\u{0430}'), Script::CYRILLIC);
+}
+
+#[test]
+fn is_zero_width() {
+    let adapter = Adapter::new();
+    for c in ['\u{200B}', '\u{200C}', '\u{200D}', '\u{FEFF}', '\u{2060}'] {
+        assert!(adapter.is_zero_width(c), "U+{:04X}", u32::from(c));
+    }
+    assert!(!adapter.is_zero_width('a'));
+    assert!(!adapter.is_zero_width(' '));
+}
+
+#[test]
+fn map_normalize_form() {
+    let adapter = Adapter::new();
+    let cases = [
+        (NormalizationForm::Nfc, "caf\u{E9}"),
+        (NormalizationForm::Nfd, "cafe\u{301}"),
+        (NormalizationForm::Nfkc, "caf\u{E9}"),
+        (NormalizationForm::Nfkd, "cafe\u{301}"),
+    ];
+    for (form, expected) in cases {
+        assert_eq!(adapter.map_normalize_form("caf\u{E9}", form), expected);
+        assert_eq!(adapter.map_normalize_form("cafe\u{301}", form), expected);
+        // The mapping already decomposes U+FB01 LATIN SMALL LIGATURE FI, so
+        // the form doesn't matter.
+        assert_eq!(adapter.map_normalize_form("\u{FB01}", form), "fi");
+    }
+    assert_eq!(
+        adapter.map_normalize_form("Caf\u{E9}", NormalizationForm::Nfc),
+        adapter
+            .map_normalize("Caf\u{E9}".chars())
+            .collect::<String>()
+    );
+}
+
+#[test]
+fn transparent_count() {
+    let adapter = Adapter::new();
+    // BEH with FATHA and DAMMA
+    assert_eq!(
+        adapter.transparent_count(&['\u{0628}', '\u{064E}', '\u{064F}']),
+        2
+    );
+    assert_eq!(adapter.transparent_count(&['a', 'b']), 0);
+}
+
+#[test]
+fn classify_label_direction() {
+    let adapter = Adapter::new();
+    assert_eq!(
+        adapter.classify_label_direction(&['a', '1']),
+        LabelDirection::Ltr
+    );
+    // ARABIC LETTER SHEEN
+    assert_eq!(
+        adapter.classify_label_direction(&['\u{0634}', 'a']),
+        LabelDirection::Rtl
+    );
+    // HEBREW LETTER ALEF
+    assert_eq!(
+        adapter.classify_label_direction(&['\u{05D0}']),
+        LabelDirection::Rtl
+    );
+    assert_eq!(
+        adapter.classify_label_direction(&['1', 'a']),
+        LabelDirection::Invalid
+    );
+    assert_eq!(
+        adapter.classify_label_direction(&[]),
+        LabelDirection::Invalid
+    );
+}
+
+#[test]
+fn map_normalize_with_stats() {
+    let adapter = Adapter::new();
+    // Mapped A, ignored soft hyphen, deviation sharp s, disallowed
+    // noncharacter, and valid b.
+    let (output, stats) = adapter.map_normalize_with_stats("A\u{AD}\u{DF}\u{FFFF}b");
+    assert_eq!(output, "a\u{DF}\u{FFFD}b");
+    assert_eq!(stats.mapped(), 1);
+    assert_eq!(stats.ignored(), 1);
+    assert_eq!(stats.deviation(), 1);
+    assert_eq!(stats.disallowed(), 1);
+
+    // FULLWIDTH LOW LINE maps to an ASCII character that the STD3 rules
+    // disallow, so it counts the same as the ASCII character.
+    for input in ["a\u{FF3F}b", "a_b"] {
+        let (output, stats) = adapter.map_normalize_with_stats(input);
+        assert_eq!(output, "a_b");
+        assert_eq!(stats.mapped(), 0);
+        assert_eq!(stats.disallowed(), 1);
+    }
+}
+
+#[test]
+fn label_separators() {
+    let adapter = Adapter::new();
+    assert_eq!(adapter.map_normalize_keep_separators("a\u{3002}b"), "a.b");
+    assert_eq!(
+        adapter.map_normalize_keep_separators("A\u{FF0E}b\u{FF61}c.d"),
+        "a.b.c.d"
+    );
+    assert!(adapter
+        .normalize_validate("a\u{3002}b\u{FF0E}c\u{FF61}d".chars())
+        .eq("a.b.c.d".chars()));
+}
+
+#[test]
+fn mark_overrides() {
+    const OVERRIDES: &[(char, bool)] = &[('\u{0301}', false), ('a', true), ('a', false)];
+    let adapter = Adapter::new().with_mark_overrides(OVERRIDES);
+    assert!(!adapter.is_mark('\u{0301}'));
+    // The first entry takes precedence.
+    assert!(adapter.is_mark('a'));
+    assert!(adapter.is_mark('\u{0300}'));
+    assert!(Adapter::new().is_mark('\u{0301}'));
+
+    let loaded = Adapter::try_new_unstable(&BakedProvider)
+        .unwrap()
+        .with_mark_overrides(OVERRIDES);
+    assert!(!loaded.is_mark('\u{0301}'));
+    assert!(loaded.is_mark('a'));
+}
+
+#[test]
+fn map_normalize_strict_deviation() {
+    let adapter = Adapter::new();
+    assert_eq!(
+        adapter.map_normalize_strict_deviation("Example"),
+        Ok(String::from("example"))
+    );
+    let error = adapter
+        .map_normalize_strict_deviation("stra\u{DF}e")
+        .unwrap_err();
+    assert_eq!(error.character(), '\u{DF}');
+    assert_eq!(error.offset(), 4);
+    // LATIN CAPITAL LETTER SHARP S maps to a deviation character.
+    let error = adapter
+        .map_normalize_strict_deviation("a\u{1E9E}")
+        .unwrap_err();
+    assert_eq!(error.character(), '\u{1E9E}');
+    assert_eq!(error.offset(), 1);
+    assert!(adapter.map_normalize_strict_deviation("a\u{200D}").is_err());
+    // GREEK CAPITAL LETTER SIGMA maps to U+03C3, not to U+03C2.
+    assert_eq!(
+        adapter.map_normalize_strict_deviation("\u{3A3}"),
+        Ok(String::from("\u{3C3}"))
+    );
+}
+
+#[test]
+fn domain_mapped_length() {
+    let adapter = Adapter::new();
+    for domain in [
+        "example.com",
+        "B\u{FC}cher.example",
+        "a\u{3002}\u{FB01}..b",
+        "",
+    ] {
+        let labels = adapter.labels_with_spans(domain);
+        let expected =
+            labels.iter().map(|(_, label)| label.len()).sum::<usize>() + labels.len() - 1;
+        assert_eq!(adapter.domain_mapped_length(domain), expected, "{}", domain);
+    }
+}
+
+#[test]
+fn verify_joining_types() {
+    let adapter = Adapter::new();
+    assert_eq!(
+        adapter.verify_joining_types(&[
+            ('\u{0628}', JoiningType::DUAL_JOINING),  // BEH
+            ('\u{0627}', JoiningType::RIGHT_JOINING), // ALEF
+            ('\u{064E}', JoiningType::TRANSPARENT),   // FATHA
+            ('\u{200D}', JoiningType::JOIN_CAUSING),  // ZWJ
+            ('a', JoiningType::NON_JOINING),
+        ]),
+        Ok(())
+    );
+    assert_eq!(
+        adapter.verify_joining_types(&[
+            ('\u{0628}', JoiningType::DUAL_JOINING),
+            ('\u{0627}', JoiningType::LEFT_JOINING),
+        ]),
+        Err((
+            '\u{0627}',
+            JoiningType::LEFT_JOINING,
+            JoiningType::RIGHT_JOINING
+        ))
+    );
+}
+
+#[test]
+fn bidi_class_mask_difference() {
+    let adapter = Adapter::new();
+    let arabic_number = adapter.bidi_class('\u{0660}').to_mask();
+    let ltr = adapter.bidi_class('a').to_mask();
+    let label_mask = BidiClassMask(arabic_number.0 | ltr.0);
+    let disallowed = label_mask.difference(MIDDLE_LTR_MASK);
+    assert!(disallowed.intersects(arabic_number));
+    assert!(!disallowed.intersects(ltr));
+    assert!(!ltr.difference(MIDDLE_LTR_MASK).intersects(label_mask));
+}
+
+#[test]
+fn is_ascii() {
+    let adapter = Adapter::new();
+    assert!(adapter.is_ascii("example.com"));
+    assert!(adapter.is_ascii(""));
+    assert!(!adapter.is_ascii("ex\u{E4}mple.com"));
+}
+
+#[test]
+fn empty_input_and_labels() {
+    let adapter = Adapter::new();
+    assert!(adapter.map_normalize("".chars()).eq("".chars()));
+    assert!(adapter.normalize_validate("".chars()).eq("".chars()));
+    assert!(adapter.map_normalize("a..b".chars()).eq("a..b".chars()));
+    assert!(adapter.map_normalize("A..B".chars()).eq("a..b".chars()));
+}
+
+#[test]
+fn is_virama_or_pure_killer() {
+    let adapter = Adapter::new();
+    // DEVANAGARI SIGN VIRAMA has CCC 9.
+    assert!(adapter.is_virama('\u{094D}'));
+    assert!(adapter.is_virama_or_pure_killer('\u{094D}'));
+    // THAI CHARACTER YAMAKKAN is a pure killer without CCC 9.
+    assert!(!adapter.is_virama('\u{0E4E}'));
+    assert!(adapter.is_virama_or_pure_killer('\u{0E4E}'));
+    assert!(!adapter.is_virama_or_pure_killer('a'));
+}
+
+#[test]
+fn free_functions_match_methods() {
+    let adapter = Adapter::new();
+    for c in [
+        'a',
+        'A',
+        '1',
+        '-',
+        '.',
+        ' ',
+        '\u{0301}',
+        '\u{0600}',
+        '\u{0627}',
+        '\u{0628}',
+        '\u{064E}',
+        '\u{05D0}',
+        '\u{0660}',
+        '\u{094D}',
+        '\u{200C}',
+        '\u{200D}',
+        '\u{20DD}',
+        '\u{65E5}',
+        '\u{1E900}',
+        '\u{10FFFF}',
+    ] {
+        assert_eq!(super::bidi_class(c), adapter.bidi_class(c));
+        assert_eq!(super::joining_type(c), adapter.joining_type(c));
+        assert_eq!(super::is_virama(c), adapter.is_virama(c));
+        assert_eq!(super::is_mark(c), adapter.is_mark(c));
+    }
+}
+
+#[test]
+fn scripts_compatible() {
+    let adapter = Adapter::new();
+    let mut running = ALL_SCRIPTS_MASK;
+    // Common characters don't narrow the set.
+    running = adapter.scripts_compatible(running, '1').unwrap();
+    assert_eq!(running, ALL_SCRIPTS_MASK);
+    for c in ['a', 'b', '-', 'c'] {
+        running = adapter.scripts_compatible(running, c).unwrap();
+        assert!(running.contains(Script::LATIN));
+        assert!(!running.contains(Script::CYRILLIC));
+    }
+    // CYRILLIC SMALL LETTER A
+    assert_eq!(adapter.scripts_compatible(running, '\u{0430}'), None);
+
+    // Han and Hiragana resolve to Japanese.
+    let mut running = ALL_SCRIPTS_MASK;
+    for c in ['\u{65E5}', '\u{3054}'] {
+        running = adapter.scripts_compatible(running, c).unwrap();
+    }
+    assert!(running.contains(Script::JAPANESE));
+    assert!(!running.contains(Script::HAN));
+}
+
+#[test]
+fn is_uts46_valid() {
+    let adapter = Adapter::new();
+    assert!(adapter.is_uts46_valid('a'));
+    assert!(adapter.is_uts46_valid('\u{E9}'));
+    assert!(!adapter.is_uts46_valid('A'));
+    assert!(!adapter.is_uts46_valid('\0'));
+    assert!(!adapter.is_uts46_valid('_'));
+    assert!(!adapter.is_uts46_valid('\u{DF}'));
+    assert!(!adapter.is_uts46_valid('\u{AD}'));
+    // disallowed_STD3_valid
+    assert!(!adapter.is_uts46_valid('\u{2260}'));
+    assert!(!adapter.is_uts46_valid('\u{226E}'));
+    // disallowed_STD3_mapped
+    assert!(!adapter.is_uts46_valid('\u{FF3F}'));
+}
+
+#[test]
+fn validate_labels() {
+    let adapter = Adapter::new();
+    let labels = adapter.validate_labels("Example.a\u{200D}b.a\u{E000}");
+    assert_eq!(labels.len(), 3);
+    assert!(labels[0].is_valid());
+    // ZWJ without a preceding virama
+    assert!(labels[1].mapping_ok());
+    assert!(!labels[1].context_ok());
+    assert!(!labels[1].is_valid());
+    // Private use character
+    assert!(!labels[2].mapping_ok());
+
+    // ASCII and non-ASCII characters that the STD3 rules disallow
+    for domain in ["a_b", "a\u{FF3F}b", "a\u{2260}b"] {
+        assert!(!adapter.validate_labels(domain)[0].mapping_ok());
+    }
+
+    // A bidi domain with an LTR label ending in an Arabic letter.
+    let labels = adapter.validate_labels("\u{0634}\u{0628}\u{0643}\u{0629}.a\u{0628}");
+    assert!(labels[0].is_valid());
+    assert!(!labels[1].bidi_ok());
+}
+
+#[test]
+fn enclosing_mark_count() {
+    let adapter = Adapter::new();
+    // COMBINING ENCLOSING CIRCLE and COMBINING ENCLOSING SQUARE
+    assert_eq!(
+        adapter.enclosing_mark_count(&['a', '\u{20DD}', '\u{20DE}']),
+        2
+    );
+    // COMBINING ACUTE ACCENT is a nonspacing mark.
+    assert_eq!(adapter.enclosing_mark_count(&['a', '\u{0301}']), 0);
+}
+
+#[test]
+fn map_normalize_checked() {
+    let adapter = Adapter::new();
+    assert_eq!(
+        adapter.map_normalize_checked("Example"),
+        Some(String::from("example"))
+    );
+    assert_eq!(
+        adapter.map_normalize_checked("B\u{FC}cher"),
+        Some(String::from("b\u{FC}cher"))
+    );
+    assert_eq!(adapter.map_normalize_checked("a\u{E000}"), None);
+    assert_eq!(adapter.map_normalize_checked("a_b"), None);
+    // FULLWIDTH LOW LINE maps to `_`.
+    assert_eq!(adapter.map_normalize_checked("a\u{FF3F}b"), None);
+    // PARENTHESIZED DIGIT ONE maps to `(1)`.
+    assert_eq!(adapter.map_normalize_checked("\u{2474}"), None);
+}
+
+#[test]
+fn all_bidi_classes() {
+    let adapter = Adapter::new();
+    let classes = Adapter::all_bidi_classes();
+    // UAX 9 defines 23 bidi classes.
+    assert_eq!(classes.len(), 23);
+    for (i, bc) in classes.iter().enumerate() {
+        assert!(!classes[..i].contains(bc));
+    }
+    for c in ('\0'..=char::MAX).step_by(7) {
+        assert!(classes.contains(&adapter.bidi_class(c)));
+    }
+}
+
+#[test]
+fn all_joining_types() {
+    let adapter = Adapter::new();
+    let types = Adapter::all_joining_types();
+    assert_eq!(types.len(), 6);
+    assert!(types.contains(&JoiningType::TRANSPARENT));
+    assert!(JoiningType::TRANSPARENT.is_transparent());
+    assert_eq!(types.iter().filter(|jt| jt.is_transparent()).count(), 1);
+    for c in ('\0'..=char::MAX).step_by(7) {
+        assert!(types.contains(&adapter.joining_type(c)));
+    }
+}
+
+#[test]
+fn map_normalize_hash() {
+    let adapter = Adapter::new();
+    let (output, hash) = adapter.map_normalize_hash("Example.COM");
+    assert_eq!(output, "example.com");
+    assert_eq!(adapter.map_normalize_hash("example.com"), (output, hash));
+    assert_eq!(
+        adapter.map_normalize_hash("cafe\u{301}").1,
+        adapter.map_normalize_hash("CAF\u{C9}").1
+    );
+    assert_ne!(
+        adapter.map_normalize_hash("a").1,
+        adapter.map_normalize_hash("b").1
+    );
+    // Published FNV-1a test vectors
+    assert_eq!(adapter.map_normalize_hash("").1, 0xcbf2_9ce4_8422_2325);
+    assert_eq!(adapter.map_normalize_hash("a").1, 0xaf63_dc4c_8601_ec8c);
+}
+
+#[test]
+fn bidi_domain_info() {
+    let adapter = Adapter::new();
+    let info = adapter.bidi_domain_info("example.\u{0634}\u{0628}\u{0643}\u{0629}");
+    assert!(info.is_bidi());
+    assert_eq!(info.direction(), LabelDirection::Ltr);
+    let info = adapter.bidi_domain_info("\u{0634}\u{0628}\u{0643}\u{0629}.example");
+    assert!(info.is_bidi());
+    assert_eq!(info.direction(), LabelDirection::Rtl);
+    let info = adapter.bidi_domain_info("example.com");
+    assert!(!info.is_bidi());
+    assert_eq!(info.direction(), LabelDirection::Ltr);
+    let info = adapter.bidi_domain_info("123");
+    assert!(!info.is_bidi());
+    assert_eq!(info.direction(), LabelDirection::Invalid);
+}
+
+#[test]
+fn mixed_digit_scripts() {
+    let adapter = Adapter::new();
+    // ASCII and Devanagari digits
+    assert!(adapter.mixed_digit_scripts(&['1', '\u{0967}']));
+    // ASCII and MATHEMATICAL BOLD DIGIT ONE, both Common
+    assert!(adapter.mixed_digit_scripts(&['1', '\u{1D7CF}']));
+    // MATHEMATICAL BOLD DIGIT ONE and MATHEMATICAL DOUBLE-STRUCK DIGIT ONE
+    assert!(adapter.mixed_digit_scripts(&['\u{1D7CF}', '\u{1D7D9}']));
+    assert!(!adapter.mixed_digit_scripts(&['1', '2', 'a', '9', '0']));
+    assert!(!adapter.mixed_digit_scripts(&['\u{0966}', '\u{096F}', 'a']));
+    assert!(!adapter.mixed_digit_scripts(&['\u{1D7CE}', '\u{1D7D7}']));
+    assert!(!adapter.mixed_digit_scripts(&['a', 'b']));
+}
+
+#[test]
+fn decimal_digits_in_complete_sets() {
+    // digit_zero relies on every run of decimal digits consisting of
+    // complete sets of ten.
+    let general_category = icu_properties::maps::general_category();
+    let mut run = 0;
+    for c in '\0'..=char::MAX {
+        if general_category.get(c) == GeneralCategory::DecimalNumber {
+            run += 1;
+        } else {
+            assert_eq!(
+                run % 10,
+                0,
+                "run of digits ending before U+{:04X}",
+                u32::from(c)
+            );
+            run = 0;
+        }
+    }
+    let adapter = Adapter::new();
+    assert_eq!(adapter.digit_zero('7'), u32::from('0'));
+    assert_eq!(adapter.digit_zero('\u{1D7D7}'), 0x1D7CE);
+    assert_eq!(adapter.digit_zero('\u{1D7D8}'), 0x1D7D8);
+}
+
+#[test]
+fn map_normalize_into_chars() {
+    let adapter = Adapter::new();
+    let mut buffer = Vec::new();
+    for input in [
+        "Example",
+        "Stra\u{DF}e",
+        "\u{FB01}\u{AD}",
+        "",
+        "caf\u{E9}.com",
+    ] {
+        adapter.map_normalize_into_chars(input, &mut buffer);
+        assert!(buffer
+            .iter()
+            .copied()
+            .eq(adapter.map_normalize(input.chars())));
+    }
+    adapter.map_normalize_into_chars("Example", &mut buffer);
+    let capacity = buffer.capacity();
+    adapter.map_normalize_into_chars("ab", &mut buffer);
+    assert_eq!(buffer, ['a', 'b']);
+    assert_eq!(buffer.capacity(), capacity);
+}
+
+#[test]
+fn mapping_invariants() {
+    let adapter = Adapter::new();
+    for input in [
+        "Example.com",
+        "Stra\u{DF}e",
+        "\u{3A3}\u{3C2}",
+        "a\u{200D}b",
+        "cafe\u{301}",
+        "",
+    ] {
+        assert_eq!(
+            adapter.assert_mapping_invariants(input),
+            Ok(()),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn contextj_ok() {
+    let adapter = Adapter::new();
+    // Persian "mi-khaham" with ZWNJ between YEH (D) and KHAH (D)
+    let persian: Vec<char> = "\u{0645}\u{06CC}\u{200C}\u{062E}\u{0648}\u{0627}\u{0647}\u{0645}"
+        .chars()
+        .collect();
+    assert!(adapter.contextj_ok(&persian));
+    // The nearest non-transparent character before ZWNJ counts, not the
+    // first one in the label.
+    assert!(adapter.contextj_ok(&['1', '\u{0645}', '\u{06CC}', '\u{200C}', '\u{062E}']));
+    assert!(!adapter.contextj_ok(&['\u{0628}', 'a', '\u{200C}', '\u{0628}']));
+    // Transparent FATHA between BEH and ZWNJ is skipped.
+    assert!(adapter.contextj_ok(&['\u{0628}', '\u{064E}', '\u{200C}', '\u{0628}']));
+    // Devanagari KA, VIRAMA, ZWJ, SSA
+    assert!(adapter.contextj_ok(&['\u{0915}', '\u{094D}', '\u{200D}', '\u{0937}']));
+    assert!(adapter.contextj_ok(&['\u{0915}', '\u{094D}', '\u{200C}', '\u{0937}']));
+    assert!(!adapter.contextj_ok(&['\u{200C}']));
+    assert!(!adapter.contextj_ok(&['\u{200D}']));
+    assert!(!adapter.contextj_ok(&['a', '\u{200D}', 'b']));
+
+    let labels = adapter.validate_labels(&persian.iter().collect::<String>());
+    assert!(labels[0].context_ok());
+}
+
+#[test]
+fn unicode_version_of_data() {
+    assert_eq!(UNICODE_VERSION, (15, 1, 0));
+    let adapter = Adapter::new();
+    let general_category = icu_properties::maps::general_category();
+    // CJK UNIFIED IDEOGRAPH-2EBF0 is new in Unicode 15.1.
+    assert_eq!(
+        general_category.get('\u{2EBF0}'),
+        GeneralCategory::OtherLetter
+    );
+    assert_eq!(adapter.script('\u{2EBF0}'), Script::HAN);
+    assert!(adapter
+        .map_normalize("\u{2EBF0}".chars())
+        .eq("\u{2EBF0}".chars()));
+    // CYRILLIC CAPITAL LETTER TJE is new in Unicode 16.0.
+    assert_eq!(
+        general_category.get('\u{1C89}'),
+        GeneralCategory::Unassigned
+    );
+    assert!(adapter
+        .map_normalize("\u{1C89}".chars())
+        .eq("\u{FFFD}".chars()));
+}
+
+/// Combines the baked data of `icu_normalizer` and `icu_properties` into
+/// one provider for `Adapter::try_new_unstable`.
+struct BakedProvider;
+
+/// Like `BakedProvider` but with Prepended_Concatenation_Mark replaced by
+/// a set that contains only `a`.
+struct PatchedProvider;
+
+macro_rules! impl_baked_provider {
+    ($provider:ident $(, $rest:ident)*; $krate:ident: $($marker:ident),+) => {
+        $(
+            impl DataProvider<$krate::provider::$marker> for $provider {
+                fn load(
+                    &self,
+                    req: icu_provider::DataRequest,
+                ) -> Result<icu_provider::DataResponse<$krate::provider::$marker>, icu_provider::DataError>
+                {
+                    $krate::provider::Baked.load(req)
+                }
+            }
+        )+
+        impl_baked_provider!($($rest),*; $krate: $($marker),+);
+    };
+    (; $krate:ident: $($marker:ident),+) => {};
+}
+
+impl_baked_provider!(BakedProvider, PatchedProvider; icu_normalizer:
+    CanonicalDecompositionDataV1Marker,
+    CanonicalDecompositionTablesV1Marker,
+    CompatibilityDecompositionSupplementV1Marker,
+    CompatibilityDecompositionTablesV1Marker,
+    Uts46DecompositionSupplementV1Marker,
+    CanonicalCompositionsV1Marker
+);
+
+impl_baked_provider!(BakedProvider, PatchedProvider; icu_properties:
+    GeneralCategoryV1Marker,
+    BidiClassV1Marker,
+    JoiningTypeV1Marker,
+    IndicSyllabicCategoryV1Marker,
+    FullCompositionExclusionV1Marker,
+    ScriptWithExtensionsPropertyV1Marker
+);
+
+impl_baked_provider!(BakedProvider; icu_properties: PrependedConcatenationMarkV1Marker);
+
+impl DataProvider<icu_properties::provider::PrependedConcatenationMarkV1Marker>
+    for PatchedProvider
+{
+    fn load(
+        &self,
+        _req: icu_provider::DataRequest,
+    ) -> Result<
+        icu_provider::DataResponse<icu_properties::provider::PrependedConcatenationMarkV1Marker>,
+        icu_provider::DataError,
+    > {
+        let set = icu_collections::codepointinvlist::CodePointInversionList::try_from_inversion_list_slice(
+            &[0x61, 0x62],
+        )
+        .unwrap();
+        Ok(icu_provider::DataResponse {
+            metadata: Default::default(),
+            payload: Some(icu_provider::DataPayload::from_owned(
+                icu_properties::provider::PropertyCodePointSetV1::InversionList(set),
+            )),
+        })
+    }
+}
+
+#[test]
+fn try_new_unstable() {
+    let loaded = Adapter::try_new_unstable(&BakedProvider).unwrap();
+    let compiled = Adapter::new();
+    for input in [
+        "Example.com",
+        "Stra\u{DF}e",
+        "\u{FB01}\u{AD}",
+        "a\u{E000}",
+        "cafe\u{301}",
+    ] {
+        assert!(loaded
+            .map_normalize(input.chars())
+            .eq(compiled.map_normalize(input.chars())));
+        assert!(loaded
+            .normalize_validate(input.chars())
+            .eq(compiled.normalize_validate(input.chars())));
+        assert_eq!(loaded.is_nfc(input), compiled.is_nfc(input));
+        for form in [
+            NormalizationForm::Nfc,
+            NormalizationForm::Nfd,
+            NormalizationForm::Nfkc,
+            NormalizationForm::Nfkd,
+        ] {
+            assert_eq!(
+                loaded.map_normalize_form(input, form),
+                compiled.map_normalize_form(input, form)
+            );
+        }
+    }
+    for c in [
+        'a', '1', '\u{0301}', '\u{0600}', '\u{0628}', '\u{094D}', '\u{0958}', '\u{0E4E}',
+        '\u{65E5}',
+    ] {
+        assert_eq!(loaded.bidi_class(c), compiled.bidi_class(c));
+        assert_eq!(loaded.joining_type(c), compiled.joining_type(c));
+        assert_eq!(loaded.is_virama(c), compiled.is_virama(c));
+        assert_eq!(loaded.is_mark(c), compiled.is_mark(c));
+        assert_eq!(loaded.script(c), compiled.script(c));
+        assert_eq!(
+            loaded.is_virama_or_pure_killer(c),
+            compiled.is_virama_or_pure_killer(c)
+        );
+        assert_eq!(
+            loaded.is_prepended_concatenation_mark(c),
+            compiled.is_prepended_concatenation_mark(c)
+        );
+        assert_eq!(
+            loaded.is_full_composition_exclusion(c),
+            compiled.is_full_composition_exclusion(c)
+        );
+    }
+    assert_eq!(
+        loaded.validate_labels("example.\u{0634}\u{0628}\u{0643}\u{0629}"),
+        compiled.validate_labels("example.\u{0634}\u{0628}\u{0643}\u{0629}")
+    );
+}
+
+#[test]
+fn reload() {
+    const OVERRIDES: &[(char, bool)] = &[('a', true)];
+    let mut adapter = Adapter::new().with_mark_overrides(OVERRIDES);
+    adapter.reload(&PatchedProvider).unwrap();
+    assert!(adapter.is_prepended_concatenation_mark('a'));
+    assert!(!adapter.is_prepended_concatenation_mark('\u{0600}'));
+    assert!(adapter.is_mark('a'));
+    adapter.reload(&BakedProvider).unwrap();
+    assert!(!adapter.is_prepended_concatenation_mark('a'));
+    assert!(adapter.is_prepended_concatenation_mark('\u{0600}'));
+    assert!(adapter.is_mark('a'));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn try_new_with_buffer_provider_missing_data() {
+    struct EmptyProvider;
+
+    impl icu_provider::BufferProvider for EmptyProvider {
+        fn load_buffer(
+            &self,
+            key: icu_provider::DataKey,
+            req: icu_provider::DataRequest,
+        ) -> Result<icu_provider::DataResponse<icu_provider::BufferMarker>, icu_provider::DataError>
+        {
+            Err(icu_provider::DataErrorKind::MissingDataKey.with_req(key, req))
+        }
+    }
+
+    let error = Adapter::try_new_with_buffer_provider(&EmptyProvider)
+        .err()
+        .unwrap();
+    assert!(!alloc::format!("{}", error).is_empty());
+}
+
+#[test]
+fn str_variants_match_iterators() {
+    let adapter = Adapter::new();
+    for input in [
+        "example.com",
+        "a_b.com",
+        "Example.com",
+        "A\u{E9}",
+        "x\u{FC}Y",
+        "\u{E9}X",
+        "B\u{FC}cher.example",
+        "stra\u{DF}e.de",
+        "\u{AD}x",
+        "cafe\u{301}",
+        "\u{65E5}\u{672C}.jp",
+        "a\u{200D}b",
+        "\u{FF21}",
+        "a\u{E000}b",
+        "a\u{1}B",
+        "",
+    ] {
+        let mapped: String = adapter.map_normalize(input.chars()).collect();
+        assert_eq!(adapter.map_normalize_str(input), mapped, "{}", input);
+        let validated: String = adapter.normalize_validate(input.chars()).collect();
+        assert_eq!(
+            adapter.normalize_validate_str(input),
+            validated,
+            "{}",
+            input
+        );
+    }
+    assert!(matches!(
+        adapter.map_normalize_str("example.com"),
+        Cow::Borrowed(_)
+    ));
+    assert!(matches!(
+        adapter.map_normalize_str("Example.COM"),
+        Cow::Owned(s) if s == "example.com"
+    ));
+    assert!(matches!(
+        adapter.normalize_validate_str("Example.COM"),
+        Cow::Owned(s) if s == "example.com"
+    ));
+    assert!(matches!(
+        adapter.map_normalize_str("b\u{FC}cher"),
+        Cow::Borrowed(_)
+    ));
+    assert!(matches!(
+        adapter.normalize_validate_str("b\u{FC}cher"),
+        Cow::Borrowed(_)
+    ));
+    assert!(matches!(
+        adapter.map_normalize_str("B\u{FC}cher"),
+        Cow::Owned(_)
+    ));
+}
+
+#[test]
+fn map_normalize_transitional() {
+    let adapter = Adapter::new();
+    let cases = [
+        ("stra\u{DF}e.de", "strasse.de"),
+        ("STRA\u{1E9E}E", "strasse"),
+        (
+            "\u{3BF}\u{3B4}\u{3BF}\u{3C2}",
+            "\u{3BF}\u{3B4}\u{3BF}\u{3C3}",
+        ),
+        (
+            "\u{39F}\u{394}\u{39F}\u{3A3}",
+            "\u{3BF}\u{3B4}\u{3BF}\u{3C3}",
+        ),
+        ("a\u{200D}b\u{200C}c", "abc"),
+        // The joiner is removed before composition.
+        ("e\u{200C}\u{301}", "\u{E9}"),
+        // The second s composes with the acute accent.
+        ("\u{DF}\u{301}", "s\u{15B}"),
+        ("Example", "example"),
+    ];
+    for (input, expected) in cases {
+        let output: String = adapter.map_normalize_transitional(input.chars()).collect();
+        assert_eq!(output, expected, "{}", input);
+        assert!(adapter.is_nfc(&output));
+    }
+    // Nontransitional processing remains the default.
+    assert!(adapter
+        .map_normalize("stra\u{DF}e".chars())
+        .eq("stra\u{DF}e".chars()));
+}